@@ -38,12 +38,28 @@ fn search_issues_iter() {
 
                     match error {
                         Error::Request(error) => eprintln!("{:#?}", error),
-                        Error::Deserialize { error, url, bytes } => {
+                        Error::Deserialize(error) => {
                             eprintln!(
                                 "URL\n{}\nError:\n{}\nData:\n{}",
-                                url,
+                                error.uri(),
                                 error,
-                                std::str::from_utf8(bytes.as_slice()).unwrap()
+                                std::str::from_utf8(error.bytes()).unwrap()
+                            )
+                        }
+                        Error::Response(error) => {
+                            eprintln!(
+                                "URL\n{}\nStatus:\n{}\nData:\n{}",
+                                error.uri(),
+                                error.status_code(),
+                                std::str::from_utf8(error.bytes()).unwrap()
+                            )
+                        }
+                        Error::BodyRead(error) => {
+                            eprintln!(
+                                "URL\n{}\nError:\n{}\nReceived:\n{}",
+                                error.uri(),
+                                error,
+                                String::from_utf8_lossy(error.bytes())
                             )
                         }
                     }
@@ -1,4 +1,7 @@
 use async_trait::async_trait;
+use awaur::endpoints::{
+    endpoint, ApiResponse, BodyReadError, DeserializeError, RequestError, ResponseError,
+};
 use awaur::paginator::{PaginatedStream, PaginationDelegate};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -7,24 +10,25 @@ pub const GITHUB_API_BASE: &str = "https://api.github.com/";
 
 pub struct Client {
     inner: surf::Client,
+    base: surf::Url,
 }
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("failed to make a request: {0:?}")]
-    Request(surf::Error),
-    #[error("failed to deserialize response data from '{url}': {error:?}")]
-    Deserialize {
-        error: serde_json::Error,
-        url: surf::Url,
-        bytes: Vec<u8>,
-    },
+    Request(RequestError<surf::Error>),
+    #[error(transparent)]
+    Deserialize(#[from] DeserializeError),
+    #[error(transparent)]
+    Response(#[from] ResponseError),
+    #[error(transparent)]
+    BodyRead(#[from] BodyReadError),
 }
 
 // It would seem that the `http-types` crate is very silly and doesn't implement
 // `Error` for their error types. Who needs standards anyway?
-impl From<surf::Error> for Error {
-    fn from(other: surf::Error) -> Self {
+impl From<RequestError<surf::Error>> for Error {
+    fn from(other: RequestError<surf::Error>) -> Self {
         Self::Request(other)
     }
 }
@@ -36,9 +40,9 @@ impl Client {
     where
         U: AsRef<str>,
     {
+        let base = surf::Url::parse(base.as_ref())?;
         let mut config = surf::Config::new();
 
-        config = config.set_base_url(surf::Url::parse(base.as_ref())?);
         config = config.add_header("Accept", "application/vnd.github.v3+json")?;
 
         if let Some(token) = token {
@@ -47,20 +51,24 @@ impl Client {
 
         Ok(Self {
             inner: config.try_into()?,
+            base,
         })
     }
 
-    pub async fn search_issues(&self, params: &IssueSearchParams) -> Result<IssueSearchResponse> {
-        let mut request = self.inner.get("search/issues");
-        request = request.query(params)?;
-        let request = request.build();
-        let url = request.url().to_owned();
-        let mut response = self.inner.send(request).await?;
-        let bytes = response.body_bytes().await?;
-        let value = serde_json::from_slice(bytes.as_slice())
-            .map_err(|error| Error::Deserialize { error, url, bytes })?;
-
-        Ok(value)
+    pub async fn search_issues(
+        &self,
+        params: &IssueSearchParams,
+    ) -> Result<ApiResponse<IssueSearchResponse>> {
+        // The `surf::Client` implements `awaur::endpoints::Backend` with the
+        // "surf" feature, so it can be given directly to the macro.
+        let client = &self.inner;
+        let base = &self.base;
+
+        endpoint! {
+            client GET,
+            uri: base / "search/issues",
+            params: params,
+        }
     }
 
     pub fn search_issues_iter(
@@ -142,7 +150,7 @@ impl PaginationDelegate for IssueSearchDelegate<'_> {
         // that was made.
         self.total_count = Some(value.total_count);
 
-        Ok(value.items)
+        Ok(value.into_value().items)
     }
 
     fn offset(&self) -> usize {
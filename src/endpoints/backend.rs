@@ -0,0 +1,302 @@
+use async_trait::async_trait;
+use futures_lite::io::AsyncRead;
+
+use crate::endpoints::RequestErrorKind;
+
+/// The HTTP client that the [`endpoint!`] macro sends its requests with.
+///
+/// This is the only part of an HTTP client that the macro depends on: sending
+/// a fully built [`http::Request`] and receiving an [`http::Response`], the
+/// status, headers, and body reader of which are then used to build either an
+/// [`ApiResponse`] or an error. Implementations for [`isahc::HttpClient`] and
+/// [`surf::Client`] are provided behind the `isahc` and `surf` features. To
+/// use any other client, implement this trait on it (or on a wrapper of it).
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+/// [`ApiResponse`]: crate::endpoints::ApiResponse
+/// [`isahc::HttpClient`]: https://docs.rs/isahc/latest/isahc/struct.HttpClient.html
+/// [`surf::Client`]: https://docs.rs/surf/latest/surf/struct.Client.html
+#[async_trait]
+pub trait Backend {
+    /// The error that can occur when the request fails to send, or the
+    /// response fails to be received. The [`endpoint!`] macro wraps it in a
+    /// [`RequestError`], so the error type of any endpoint function that uses
+    /// this backend must implement `From<RequestError<Self::Error>>`.
+    ///
+    /// [`RequestError`]: crate::endpoints::RequestError
+    type Error;
+    /// The reader that the body of the response will be read from.
+    type Body: AsyncRead + Unpin + Send;
+
+    /// Send the request, resolving to the response once the status and
+    /// headers have been received. The body may not have been received yet.
+    async fn send_request(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Self::Body>, Self::Error>;
+
+    /// Classify an error that was returned by [`Self::send_request`], for
+    /// [`RequestError::kind`]. Unless this is implemented, every error is
+    /// [`RequestErrorKind::Other`].
+    ///
+    /// [`RequestError::kind`]: crate::endpoints::RequestError::kind
+    fn error_kind(&self, _error: &Self::Error) -> RequestErrorKind {
+        RequestErrorKind::Other
+    }
+}
+
+/// Classify an I/O error, for backends that report the failures of the
+/// underlying connection as one.
+#[cfg(feature = "surf")]
+fn io_error_kind(error: &std::io::Error) -> RequestErrorKind {
+    use std::io::ErrorKind;
+
+    match error.kind() {
+        ErrorKind::TimedOut => RequestErrorKind::Timeout,
+        ErrorKind::ConnectionRefused
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::NotConnected
+        | ErrorKind::AddrNotAvailable => RequestErrorKind::Connect,
+        _ => RequestErrorKind::Other,
+    }
+}
+
+#[cfg(feature = "isahc")]
+#[async_trait]
+impl Backend for isahc::HttpClient {
+    type Error = isahc::Error;
+    type Body = isahc::AsyncBody;
+
+    async fn send_request(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Self::Body>, Self::Error> {
+        #[allow(unused_mut)]
+        let mut response = self.send_async(request).await?;
+
+        // Metrics are only collected if they were enabled on the client. The
+        // total is left to the `endpoint!` macro, because the body has not been
+        // read yet.
+        #[cfg(feature = "timings")]
+        if let Some(metrics) = isahc::ResponseExt::metrics(&response) {
+            // Unlike the name lookup, the connection phases are reported by
+            // themselves, rather than from the start of the request.
+            let name_lookup = metrics.name_lookup_time();
+            let connect = name_lookup + metrics.connect_time() + metrics.secure_connect_time();
+            let timings = crate::endpoints::Timings {
+                name_lookup: Some(name_lookup),
+                connect: Some(connect),
+                first_byte: Some(metrics.transfer_start_time()),
+                total: None,
+            };
+            response.extensions_mut().insert(timings);
+        }
+
+        Ok(response)
+    }
+
+    fn error_kind(&self, error: &Self::Error) -> RequestErrorKind {
+        use isahc::error::ErrorKind;
+
+        match error.kind() {
+            ErrorKind::Timeout => RequestErrorKind::Timeout,
+            ErrorKind::ConnectionFailed | ErrorKind::NameResolution => RequestErrorKind::Connect,
+            ErrorKind::BadClientCertificate
+            | ErrorKind::BadServerCertificate
+            | ErrorKind::TlsEngine => RequestErrorKind::Tls,
+            _ => RequestErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(feature = "surf")]
+#[async_trait]
+impl Backend for surf::Client {
+    type Error = surf::Error;
+    type Body = surf::Body;
+
+    async fn send_request(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Self::Body>, Self::Error> {
+        let (parts, body) = request.into_parts();
+        let method = parts.method.as_str().parse()?;
+        let url = surf::Url::parse(&parts.uri.to_string())?;
+        let mut outgoing = surf::Request::new(method, url);
+
+        for (name, value) in parts.headers.iter() {
+            outgoing.append_header(
+                name.as_str(),
+                String::from_utf8_lossy(value.as_bytes()).as_ref(),
+            );
+        }
+
+        outgoing.set_body(body);
+
+        let mut incoming = self.send(outgoing).await?;
+        let mut response = http::Response::new(incoming.take_body());
+
+        // Use of unwrap:
+        // Every status code that `surf` can represent is in the range that
+        // `http` accepts.
+        *response.status_mut() = http::StatusCode::from_u16(incoming.status().into()).unwrap();
+
+        // Skip any header that `http` considers invalid, rather than failing
+        // on a response that was otherwise received successfully.
+        for (name, values) in incoming.iter() {
+            for value in values.iter() {
+                let name = http::header::HeaderName::from_bytes(name.as_str().as_bytes());
+                let value = http::header::HeaderValue::from_str(value.as_str());
+
+                if let (Ok(name), Ok(value)) = (name, value) {
+                    response.headers_mut().append(name, value);
+                }
+            }
+        }
+
+        Ok(response)
+    }
+
+    fn error_kind(&self, error: &Self::Error) -> RequestErrorKind {
+        // The error from the underlying client is type-erased, but a failure
+        // of the connection is reported as an I/O error by every one of them.
+        match error.downcast_ref::<std::io::Error>() {
+            Some(error) => io_error_kind(error),
+            None => RequestErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "isahc", feature = "surf")))]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    use serde::Deserialize;
+
+    use crate::endpoints::{
+        endpoint, ApiResponse, Backend, BodyReadError, BoxApiError, DeserializeError, RequestError,
+        RequestErrorKind, ResponseError,
+    };
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Greeting {
+        message: String,
+    }
+
+    /// Serve exactly one request on a random local port, responding with a
+    /// fixed JSON body. The handle resolves to the head of the request that was
+    /// received.
+    fn serve_once() -> (url::Url, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = url::Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut head = Vec::new();
+            let mut byte = [0];
+
+            while !head.ends_with(b"\r\n\r\n") {
+                stream.read_exact(&mut byte).unwrap();
+                head.push(byte[0]);
+            }
+
+            let body = r#"{"message":"hello"}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: \
+                 {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+
+            String::from_utf8(head).unwrap()
+        });
+
+        (base, handle)
+    }
+
+    async fn get_greeting<C, E>(client: &C, base: &url::Url) -> Result<ApiResponse<Greeting>, E>
+    where
+        C: Backend + Sync,
+        E: From<RequestError<C::Error>>
+            + From<DeserializeError>
+            + From<ResponseError>
+            + From<BodyReadError>,
+    {
+        endpoint! {
+            client GET,
+            uri: base / "greeting",
+        }
+    }
+
+    #[cfg(feature = "isahc")]
+    #[test]
+    fn test_isahc_backend() {
+        let (base, handle) = serve_once();
+        let client = isahc::HttpClient::new().unwrap();
+        let response =
+            futures_lite::future::block_on(get_greeting::<_, BoxApiError>(&client, &base)).unwrap();
+
+        assert_eq!(response.message, "hello");
+        assert!(handle
+            .join()
+            .unwrap()
+            .starts_with("GET /greeting HTTP/1.1\r\n"));
+    }
+
+    #[cfg(feature = "surf")]
+    #[test]
+    fn test_surf_backend() {
+        let (base, handle) = serve_once();
+        let client = surf::Client::new();
+        let response =
+            futures_lite::future::block_on(get_greeting::<_, BoxApiError>(&client, &base)).unwrap();
+
+        assert_eq!(response.message, "hello");
+        assert!(handle
+            .join()
+            .unwrap()
+            .starts_with("GET /greeting HTTP/1.1\r\n"));
+    }
+
+    #[cfg(feature = "isahc")]
+    #[test]
+    fn test_isahc_error_kind() {
+        // Nothing is listening on the port once the listener is dropped, so
+        // the connection is refused.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = url::Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        drop(listener);
+
+        let client = isahc::HttpClient::new().unwrap();
+        let error = futures_lite::future::block_on(get_greeting::<_, BoxApiError>(&client, &base))
+            .unwrap_err();
+        let error = error.downcast_ref::<RequestError<BoxApiError>>().unwrap();
+
+        assert_eq!(error.kind(), RequestErrorKind::Connect);
+        assert_eq!(error.uri().as_str(), format!("{}greeting", base));
+        assert_eq!(
+            client.error_kind(&isahc::error::ErrorKind::Timeout.into()),
+            RequestErrorKind::Timeout
+        );
+    }
+
+    #[cfg(feature = "surf")]
+    #[test]
+    fn test_surf_error_kind() {
+        use std::io;
+
+        let client = surf::Client::new();
+        let kind_of = |kind: io::ErrorKind| client.error_kind(&io::Error::from(kind).into());
+
+        assert_eq!(kind_of(io::ErrorKind::TimedOut), RequestErrorKind::Timeout);
+        assert_eq!(
+            kind_of(io::ErrorKind::ConnectionRefused),
+            RequestErrorKind::Connect
+        );
+        assert_eq!(kind_of(io::ErrorKind::Other), RequestErrorKind::Other);
+    }
+}
@@ -0,0 +1,155 @@
+use std::future::Future;
+
+use crate::endpoints::ApiResponse;
+
+/// Request every page of a collection from an endpoint, and concatenate the
+/// items of all of them. This is a shortcut for small collections, where a
+/// [`PaginationDelegate`] would be more ceremony than it is worth.
+///
+/// The function `get_page` is called with the offset of the first item on the
+/// page and the `page_size`, which are intended to be passed on to an
+/// endpoint that returns a page of items. The collection is considered to be
+/// exhausted when a page has fewer than `page_size` items, or when the offset
+/// reaches the total reported by [`ApiResponse::total_count`], whichever comes
+/// first.
+///
+/// No more than `max_pages` pages are requested, even if the collection has
+/// not been exhausted by then, which protects against a server that never
+/// returns a short page. The items are returned with whether that happened,
+/// which is `true` if the collection was truncated by `max_pages`, and there
+/// may be more items than were returned. If any page fails, the error is
+/// returned immediately, and the items from every page before it are
+/// discarded.
+///
+/// # Panics
+///
+/// If `page_size` is zero, because every page would look like the last.
+///
+/// [`PaginationDelegate`]: crate::paginator::PaginationDelegate
+pub async fn collect_pages<T, E, F, Fut>(
+    page_size: usize,
+    max_pages: usize,
+    mut get_page: F,
+) -> Result<(Vec<T>, bool), E>
+where
+    F: FnMut(usize, usize) -> Fut,
+    Fut: Future<Output = Result<ApiResponse<Vec<T>>, E>>,
+{
+    assert!(page_size > 0, "the page size must not be zero");
+
+    let mut items = Vec::new();
+
+    for _ in 0..max_pages {
+        let response = get_page(items.len(), page_size).await?;
+        let total = response.total_count();
+        let page = response.into_value();
+        let received = page.len();
+
+        items.extend(page);
+
+        if received < page_size || total.is_some_and(|total| items.len() >= total) {
+            return Ok((items, false));
+        }
+    }
+
+    Ok((items, true))
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::collect_pages;
+    use crate::endpoints::{endpoint, ApiResponse, RequestRecorder, TestError};
+
+    async fn get_items(
+        client: &RequestRecorder,
+        base: &url::Url,
+        offset: usize,
+        limit: usize,
+    ) -> Result<ApiResponse<Vec<u32>>, TestError> {
+        #[derive(serde::Serialize)]
+        struct Params {
+            offset: usize,
+            limit: usize,
+        }
+
+        endpoint! {
+            client GET,
+            uri: base / "items",
+            params: &Params { offset, limit },
+        }
+    }
+
+    fn page(body: &[u8]) -> http::Response<Vec<u8>> {
+        http::Response::new(body.to_vec())
+    }
+
+    #[test]
+    fn test_collect_pages() {
+        let client = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+
+        client.push_response(page(b"[0,1,2]"));
+        client.push_response(page(b"[3,4,5]"));
+        client.push_response(page(b"[6]"));
+
+        let items = futures_lite::future::block_on(collect_pages(3, 10, |offset, limit| {
+            get_items(&client, &base, offset, limit)
+        }))
+        .unwrap();
+
+        assert_eq!(items, ([0, 1, 2, 3, 4, 5, 6].into(), false));
+
+        let requests = client.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(
+            requests[2].uri,
+            "https://example.com/items?offset=6&limit=3"
+        );
+    }
+
+    #[test]
+    fn test_collect_pages_max_pages() {
+        let client = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+
+        for _ in 0..3 {
+            client.push_response(page(b"[0,1]"));
+        }
+
+        let items = futures_lite::future::block_on(collect_pages(2, 2, |offset, limit| {
+            get_items(&client, &base, offset, limit)
+        }))
+        .unwrap();
+
+        assert_eq!(items, ([0, 1, 0, 1].into(), true));
+        assert_eq!(client.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_collect_pages_max_pages_exhausted() {
+        let client = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+
+        client.push_response(page(b"[0,1]"));
+        client.push_response(page(b"[2]"));
+
+        // The last page that may be requested is short, so nothing is missing.
+        let items = futures_lite::future::block_on(collect_pages(2, 2, |offset, limit| {
+            get_items(&client, &base, offset, limit)
+        }))
+        .unwrap();
+
+        assert_eq!(items, ([0, 1, 2].into(), false));
+    }
+
+    #[test]
+    #[should_panic(expected = "the page size must not be zero")]
+    fn test_collect_pages_zero_page_size() {
+        let client = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+
+        let _ = futures_lite::future::block_on(collect_pages(0, 2, |offset, limit| {
+            get_items(&client, &base, offset, limit)
+        }));
+    }
+}
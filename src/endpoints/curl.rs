@@ -0,0 +1,171 @@
+use std::fmt::Write;
+
+use async_trait::async_trait;
+use http::header::{HeaderName, AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION};
+
+use crate::endpoints::backend::Backend;
+use crate::endpoints::errors::RequestErrorKind;
+
+/// The headers that are redacted by default, because they usually carry
+/// credentials.
+pub const SENSITIVE_HEADERS: [HeaderName; 3] = [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE];
+
+/// Render `request` as an equivalent `curl` command, for reproducing it
+/// outside of the program, such as when an endpoint misbehaves. The value of
+/// every header named in `redact` is replaced with `<redacted>`, so that the
+/// command can be shared; use [`SENSITIVE_HEADERS`] unless there are others.
+///
+/// Every argument is quoted for a POSIX shell. A body that is not valid UTF-8
+/// has its invalid bytes replaced, so the command will not send it exactly.
+pub fn to_curl<B>(request: &http::Request<B>, redact: &[HeaderName]) -> String
+where
+    B: AsRef<[u8]>,
+{
+    let mut command = format!(
+        "curl -X {} {}",
+        request.method(),
+        shell_quote(&request.uri().to_string())
+    );
+
+    for (name, value) in request.headers() {
+        let value = if redact.contains(name) {
+            "<redacted>".into()
+        } else {
+            String::from_utf8_lossy(value.as_bytes())
+        };
+        // Use of unwrap:
+        // Writing to a `String` never fails.
+        write!(
+            command,
+            " -H {}",
+            shell_quote(&format!("{}: {}", name, value))
+        )
+        .unwrap();
+    }
+
+    let body = request.body().as_ref();
+    if !body.is_empty() {
+        let body = String::from_utf8_lossy(body);
+        write!(command, " --data-binary {}", shell_quote(&body)).unwrap();
+    }
+
+    command
+}
+
+/// Quote `value` in single quotes, in which a POSIX shell interprets nothing,
+/// except a single quote, which has to end the quotes to be escaped.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Wraps another [`Backend`], rendering every request as a `curl` command with
+/// [`to_curl`] before sending it, and passing the command to a function, such
+/// as one that logs it. Endpoint functions don't need to be changed, as long
+/// as they accept any `Backend`.
+pub struct DebugCurl<B, F> {
+    inner: B,
+    log: F,
+    redact: Vec<HeaderName>,
+}
+
+impl<B, F> DebugCurl<B, F> {
+    /// Wrap the `inner` backend, calling `log` with the command for every
+    /// request, with the [`SENSITIVE_HEADERS`] redacted.
+    pub fn new(inner: B, log: F) -> Self {
+        Self {
+            inner,
+            log,
+            redact: SENSITIVE_HEADERS.to_vec(),
+        }
+    }
+
+    /// Redact the values of `headers`, instead of the [`SENSITIVE_HEADERS`].
+    pub fn with_redacted(mut self, headers: impl IntoIterator<Item = HeaderName>) -> Self {
+        self.redact = headers.into_iter().collect();
+        self
+    }
+
+    /// Reference to the inner backend.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume this backend, taking out the inner backend.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<B, F> Backend for DebugCurl<B, F>
+where
+    B: Backend + Sync,
+    F: Fn(String) + Sync,
+{
+    type Error = B::Error;
+    type Body = B::Body;
+
+    async fn send_request(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Self::Body>, Self::Error> {
+        (self.log)(to_curl(&request, &self.redact));
+
+        self.inner.send_request(request).await
+    }
+
+    fn error_kind(&self, error: &Self::Error) -> RequestErrorKind {
+        self.inner.error_kind(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_curl, SENSITIVE_HEADERS};
+
+    #[test]
+    fn test_to_curl() {
+        let request = http::Request::post("https://example.com/notes?draft=true")
+            .header("Content-Type", "application/json")
+            .header("Authorization", "Bearer secret")
+            .body(br#"{"text":"it's done"}"#.to_vec())
+            .unwrap();
+
+        assert_eq!(
+            to_curl(&request, &SENSITIVE_HEADERS),
+            "curl -X POST 'https://example.com/notes?draft=true' \
+             -H 'content-type: application/json' \
+             -H 'authorization: <redacted>' \
+             --data-binary '{\"text\":\"it'\\''s done\"}'"
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_debug_curl() {
+        use std::sync::Mutex;
+
+        use super::DebugCurl;
+        use crate::endpoints::{Backend, RequestRecorder};
+
+        let recorder = RequestRecorder::new();
+        let commands = Mutex::new(Vec::new());
+        let client = DebugCurl::new(recorder.clone(), |command| {
+            commands.lock().unwrap().push(command)
+        })
+        .with_redacted([]);
+
+        let request = http::Request::get("https://example.com/user")
+            .header("Authorization", "Bearer secret")
+            .body(Vec::new())
+            .unwrap();
+        futures_lite::future::block_on(client.send_request(request)).unwrap();
+
+        // Nothing is redacted, and the request was still sent.
+        assert_eq!(
+            *commands.lock().unwrap(),
+            ["curl -X GET 'https://example.com/user' -H 'authorization: Bearer secret'"]
+        );
+        assert_eq!(recorder.requests().len(), 1);
+    }
+}
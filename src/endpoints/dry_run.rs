@@ -0,0 +1,221 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use futures_lite::io::{AsyncRead, Cursor};
+
+use crate::endpoints::backend::Backend;
+use crate::endpoints::errors::RequestErrorKind;
+
+/// What a [`DryRun`] backend does with a request while it is enabled, rather
+/// than sending it.
+#[derive(Debug)]
+pub enum DryRunOutcome {
+    /// Respond with `200 OK` and a body of `null`. This is enough for
+    /// endpoints that return `()` or an [`Option`].
+    Null,
+    /// Respond with a copy of this response.
+    Respond(http::Response<Vec<u8>>),
+    /// Fail with [`DryRunError::DryRun`], which describes the request.
+    Fail,
+}
+
+/// Error type of a [`DryRun`] backend.
+#[derive(Debug, thiserror::Error)]
+pub enum DryRunError<E> {
+    /// The request was sent by the inner backend, and it failed.
+    #[error(transparent)]
+    Backend(E),
+    /// Dry-run mode was enabled with [`DryRunOutcome::Fail`], so the request
+    /// was never sent.
+    #[error("dry run, did not send {method} request to:\n{uri}")]
+    DryRun {
+        /// The method that the request would have been made with.
+        method: http::Method,
+        /// The URI that the request would have been made to.
+        uri: http::Uri,
+    },
+}
+
+/// Wraps another [`Backend`], so that sending requests can be switched off at
+/// runtime, such as for the `--dry-run` flag of a command-line tool. Endpoint
+/// functions don't need to be changed, as long as they accept any `Backend`.
+///
+/// While dry-run mode is enabled, it takes precedence over sending: the inner
+/// backend is never called, and every request is answered according to the
+/// [`DryRunOutcome`] instead. While it is disabled, every request is passed to
+/// the inner backend unchanged. The mode can be toggled through a shared
+/// reference, and applies to the next request that is sent.
+#[derive(Debug)]
+pub struct DryRun<B> {
+    inner: B,
+    enabled: AtomicBool,
+    outcome: DryRunOutcome,
+}
+
+impl<B> DryRun<B> {
+    /// Wrap the `inner` backend, answering requests with `outcome` while
+    /// dry-run mode is `enabled`.
+    pub fn new(inner: B, enabled: bool, outcome: DryRunOutcome) -> Self {
+        Self {
+            inner,
+            enabled: AtomicBool::new(enabled),
+            outcome,
+        }
+    }
+
+    /// Whether dry-run mode is currently enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Enable or disable dry-run mode.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst)
+    }
+
+    /// Reference to the inner backend.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume this backend, taking out the inner backend.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+/// The body of a response received through a [`DryRun`] backend, which is
+/// either from the inner backend, or canned.
+pub enum DryRunBody<B> {
+    /// The body of a response from the inner backend.
+    Backend(B),
+    /// The body of a response from the [`DryRunOutcome`].
+    Canned(Cursor<Vec<u8>>),
+}
+
+impl<B> AsyncRead for DryRunBody<B>
+where
+    B: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Backend(body) => Pin::new(body).poll_read(ctx, buf),
+            Self::Canned(body) => Pin::new(body).poll_read(ctx, buf),
+        }
+    }
+}
+
+#[async_trait]
+impl<B> Backend for DryRun<B>
+where
+    B: Backend + Sync,
+{
+    type Error = DryRunError<B::Error>;
+    type Body = DryRunBody<B::Body>;
+
+    async fn send_request(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Self::Body>, Self::Error> {
+        if !self.is_enabled() {
+            let response = self
+                .inner
+                .send_request(request)
+                .await
+                .map_err(DryRunError::Backend)?;
+
+            return Ok(response.map(DryRunBody::Backend));
+        }
+
+        let canned = match &self.outcome {
+            DryRunOutcome::Null => http::Response::new(b"null".to_vec()),
+            DryRunOutcome::Respond(response) => {
+                let mut canned = http::Response::new(response.body().clone());
+                *canned.status_mut() = response.status();
+                *canned.headers_mut() = response.headers().clone();
+                canned
+            }
+            DryRunOutcome::Fail => {
+                return Err(DryRunError::DryRun {
+                    method: request.method().clone(),
+                    uri: request.uri().clone(),
+                })
+            }
+        };
+
+        Ok(canned.map(|body| DryRunBody::Canned(Cursor::new(body))))
+    }
+
+    fn error_kind(&self, error: &Self::Error) -> RequestErrorKind {
+        match error {
+            DryRunError::Backend(error) => self.inner.error_kind(error),
+            DryRunError::DryRun { .. } => RequestErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::{DryRun, DryRunError, DryRunOutcome};
+    use crate::endpoints::{endpoint, ApiResponse, Backend, RequestRecorder, TestError};
+
+    async fn create_note<C>(
+        client: &C,
+        base: &url::Url,
+    ) -> Result<ApiResponse<u32>, TestError<DryRunError<Infallible>>>
+    where
+        C: Backend<Error = DryRunError<Infallible>> + Sync,
+    {
+        endpoint! {
+            client POST,
+            uri: base / "notes",
+            body: "hello",
+        }
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let recorder = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+        let client = DryRun::new(
+            recorder.clone(),
+            true,
+            DryRunOutcome::Respond(http::Response::new(b"0".to_vec())),
+        );
+
+        let response = futures_lite::future::block_on(create_note(&client, &base)).unwrap();
+        assert_eq!(*response, 0);
+        assert!(recorder.requests().is_empty());
+
+        let client = DryRun::new(recorder.clone(), true, DryRunOutcome::Fail);
+
+        match futures_lite::future::block_on(create_note(&client, &base)) {
+            Err(TestError::Request(error)) => match error.inner() {
+                DryRunError::DryRun { method, uri } => {
+                    assert_eq!(method, http::Method::POST);
+                    assert_eq!(uri, "https://example.com/notes");
+                }
+                DryRunError::Backend(error) => match *error {},
+            },
+            _ => panic!("expected the request to be refused"),
+        }
+        assert!(recorder.requests().is_empty());
+
+        // Once disabled, requests are sent again.
+        client.set_enabled(false);
+        recorder.push_response(http::Response::new(b"7".to_vec()));
+
+        let response = futures_lite::future::block_on(create_note(&client, &base)).unwrap();
+        assert_eq!(*response, 7);
+        assert_eq!(recorder.requests().len(), 1);
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(feature = "compression")]
+use std::io::Read;
+
+/// Decode the body of a response according to its `Content-Encoding` header,
+/// returning the decoded bytes, and the bytes as they were received if they
+/// were changed. Encodings are undone in the reverse order that they are
+/// listed, as the header requires.
+///
+/// Without the `compression` feature, or if the body uses an encoding other
+/// than `gzip` or `deflate`, the bytes are returned unchanged, and will most
+/// likely fail to deserialize. The same happens if the body is not valid for
+/// the encoding that it claims to use, so that the error that is reported
+/// contains the bytes that the server sent.
+#[doc(hidden)]
+pub fn __decode_body(headers: &http::HeaderMap, bytes: Vec<u8>) -> (Vec<u8>, Option<Vec<u8>>) {
+    #[cfg(feature = "compression")]
+    {
+        let encodings = headers
+            .get_all(http::header::CONTENT_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|encoding| encoding.trim().to_ascii_lowercase())
+            .filter(|encoding| !encoding.is_empty() && encoding != "identity")
+            .collect::<Vec<_>>();
+
+        if encodings.is_empty() {
+            return (bytes, None);
+        }
+
+        let mut decoded = None;
+
+        for encoding in encodings.iter().rev() {
+            let input = decoded.as_deref().unwrap_or(&bytes[..]);
+
+            match decode(encoding, input) {
+                Some(output) => decoded = Some(output),
+                None => return (bytes, None),
+            }
+        }
+
+        match decoded {
+            Some(decoded) => (decoded, Some(bytes)),
+            None => (bytes, None),
+        }
+    }
+
+    #[cfg(not(feature = "compression"))]
+    {
+        let _ = headers;
+        (bytes, None)
+    }
+}
+
+/// Undo a single content encoding, or `None` if it is not supported, or the
+/// input is not valid for it.
+#[cfg(feature = "compression")]
+fn decode(encoding: &str, input: &[u8]) -> Option<Vec<u8>> {
+    use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+
+    let mut output = Vec::new();
+    let result = match encoding {
+        "gzip" | "x-gzip" => GzDecoder::new(input).read_to_end(&mut output),
+        // The `deflate` encoding is supposed to be a zlib stream, but some
+        // servers send a raw deflate stream instead, so that is tried too.
+        "deflate" => ZlibDecoder::new(input)
+            .read_to_end(&mut output)
+            .or_else(|_| {
+                output.clear();
+                DeflateDecoder::new(input).read_to_end(&mut output)
+            }),
+        _ => return None,
+    };
+
+    result.ok().map(|_| output)
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+
+    use super::__decode_body;
+
+    fn headers(encoding: &str) -> http::HeaderMap {
+        let mut headers = http::HeaderMap::new();
+        headers.insert("Content-Encoding", encoding.parse().unwrap());
+        headers
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zlib(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_decode_body() {
+        let compressed = gzip(b"{\"id\":1}");
+        let (decoded, original) = __decode_body(&headers("gzip"), compressed.clone());
+        assert_eq!(decoded, b"{\"id\":1}");
+        assert_eq!(original, Some(compressed));
+
+        let compressed = gzip(&zlib(b"[]"));
+        let (decoded, _) = __decode_body(&headers("deflate, gzip"), compressed);
+        assert_eq!(decoded, b"[]");
+
+        // Unsupported, or not what it claims to be.
+        let (decoded, original) = __decode_body(&headers("br"), b"[]".to_vec());
+        assert_eq!((decoded, original), (b"[]".to_vec(), None));
+        let (decoded, original) = __decode_body(&headers("gzip"), b"[]".to_vec());
+        assert_eq!((decoded, original), (b"[]".to_vec(), None));
+    }
+}
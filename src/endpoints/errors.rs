@@ -1,30 +1,254 @@
+use crate::endpoints::retry_after::parse_retry_after;
+
 /// Error type used if an API request received a successful response, but the
 /// body bytes failed to deserialize into the expected strong-type. This
 /// contains the original bytes that failed to deserialize, for debugging
 /// purposes.
+///
+/// The type parameter is the error of the [`ResponseFormat`] that the body was
+/// deserialized with, which is [`serde_json::Error`] unless the endpoint was
+/// given another `format`.
+///
+/// [`ResponseFormat`]: crate::endpoints::ResponseFormat
 #[derive(Debug, thiserror::Error)]
 #[error("failed to deserialize a response from:\n{uri}\n{inner}")]
-pub struct DeserializeError {
+pub struct DeserializeError<E = serde_json::Error> {
     uri: url::Url,
     bytes: Vec<u8>,
+    request_id: Option<String>,
+    /// Whether the bytes are the body as it was received, before it was
+    /// decompressed, in which case the position of the error is not in them.
+    encoded: bool,
     #[source]
-    inner: serde_path_to_error::Error<serde_json::Error>,
+    inner: serde_path_to_error::Error<E>,
+}
+
+/// The [`Backend`] failed to send a request, or to receive the head of its
+/// response, so there is no status or body. This contains the error from the
+/// backend, and a classification of it, as reported by [`Backend::error_kind`],
+/// so that a timeout can be told apart from a failure to connect without
+/// knowing which backend was used.
+///
+/// [`Backend`]: crate::endpoints::Backend
+/// [`Backend::error_kind`]: crate::endpoints::Backend::error_kind
+#[derive(Debug, thiserror::Error)]
+#[error("failed to send a request ({kind}) to:\n{uri}")]
+pub struct RequestError<E> {
+    uri: url::Url,
+    request_id: Option<String>,
+    kind: RequestErrorKind,
+    #[source]
+    inner: E,
+}
+
+/// The reason that sending a request failed, as reported by
+/// [`RequestError::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum RequestErrorKind {
+    /// The request, or the connection for it, took longer than the backend
+    /// allowed.
+    #[error("timed out")]
+    Timeout,
+    /// The host could not be resolved, or a connection to it could not be
+    /// established.
+    #[error("connection failed")]
+    Connect,
+    /// The TLS handshake failed, such as because of an invalid certificate.
+    #[error("TLS error")]
+    Tls,
+    /// Any other error, or one that the backend does not classify, which can
+    /// be inspected with [`RequestError::inner`].
+    #[error("other error")]
+    Other,
+}
+
+/// A request did not complete within the `timeout` that was given to the
+/// [`endpoint!`] macro, so it was abandoned. This contains the duration that
+/// elapsed, and the URI that the request was sent to.
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+#[derive(Debug, thiserror::Error)]
+#[error("request timed out after {timeout:?} to:\n{uri}")]
+pub struct RequestTimeoutError {
+    uri: url::Url,
+    request_id: Option<String>,
+    timeout: std::time::Duration,
 }
 
 /// A request to a URI that was expected to return successfully with 200
-/// OK has failed to do so. This contains the status code that was received
-/// instead, and the bytes in the body of the response.
+/// OK (or one of the other statuses that the endpoint expects) has failed to
+/// do so. This contains the status code that was received instead, and the
+/// headers and bytes in the body of the response.
 #[derive(Debug, thiserror::Error)]
 #[error("received unsuccessful status code {status} from:\n{uri}")]
 pub struct ResponseError {
     uri: url::Url,
     bytes: Vec<u8>,
+    request_id: Option<String>,
     status: http::StatusCode,
+    headers: http::HeaderMap,
+}
+
+/// The status and headers of a response were received, but the connection
+/// failed before the whole body could be read. This contains the bytes of the
+/// body that were received before the failure, and the I/O error that caused
+/// it, classified by [`BodyReadErrorKind`].
+#[derive(Debug, thiserror::Error)]
+#[error("failed to read the body of a response ({kind}) from:\n{uri}")]
+pub struct BodyReadError {
+    uri: url::Url,
+    bytes: Vec<u8>,
+    request_id: Option<String>,
+    kind: BodyReadErrorKind,
+    #[source]
+    inner: std::io::Error,
+}
+
+/// The reason that reading the body of a response failed, as reported by
+/// [`BodyReadError::kind`]. Both a reset connection and an unexpected end of
+/// the body are likely to be transient, so a request that failed with either
+/// is usually worth retrying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum BodyReadErrorKind {
+    /// The connection was reset, aborted, or closed by the server while the
+    /// body was being received.
+    #[error("connection reset")]
+    ConnectionReset,
+    /// The body ended before all of it had been received, such as when there
+    /// were fewer bytes than the `Content-Length` said.
+    #[error("unexpected end of the body")]
+    UnexpectedEof,
+    /// Any other I/O error, which can be inspected with
+    /// [`BodyReadError::inner`].
+    #[error("I/O error")]
+    Other,
+}
+
+/// The body of a `PATCH` request with `patch_type: json_patch` was not a
+/// valid JSON Patch document, as described by [RFC 6902]. The request was never
+/// sent. This contains the serialized body, and the reason that it was
+/// rejected.
+///
+/// [RFC 6902]: https://www.rfc-editor.org/rfc/rfc6902
+#[derive(Debug, thiserror::Error)]
+#[error("invalid JSON Patch document for:\n{uri}\n{reason}")]
+pub struct JsonPatchError {
+    uri: url::Url,
+    bytes: Vec<u8>,
+    reason: String,
+}
+
+/// The `form` of a request could not be serialized as
+/// `application/x-www-form-urlencoded`, such as because it contains a nested
+/// structure or a sequence, which that format has no way to represent. The
+/// request was never sent.
+#[derive(Debug, thiserror::Error)]
+#[error("failed to serialize the form body for:\n{uri}")]
+pub struct FormError {
+    uri: url::Url,
+    #[source]
+    inner: serde_urlencoded::ser::Error,
+}
+
+/// The body of a response was valid JSON, but did not conform to the JSON
+/// Schema that the endpoint was given with `schema`. This contains the body
+/// bytes of the response, and a message for each violation of the schema.
+#[cfg(feature = "jsonschema")]
+#[derive(Debug, thiserror::Error)]
+#[error("response from:\n{uri}\ndoes not match the schema:\n{}", .messages.join("\n"))]
+pub struct SchemaError {
+    uri: url::Url,
+    bytes: Vec<u8>,
+    request_id: Option<String>,
+    messages: Vec<String>,
 }
 
+/// A record of a JSON text sequence, being read by a [`JsonSeqStream`], could
+/// not be received or deserialized.
+///
+/// [`JsonSeqStream`]: crate::endpoints::JsonSeqStream
+#[derive(Debug, thiserror::Error)]
+pub enum JsonSeqError {
+    /// Reading from the body failed. The stream ends after yielding this.
+    #[error("failed to read a JSON text sequence")]
+    Read(#[from] std::io::Error),
+    /// A whole record was received, but it failed to deserialize. This
+    /// contains the bytes of the record, without the separator. The stream
+    /// continues with the next record.
+    #[error("failed to deserialize a record of a JSON text sequence")]
+    Deserialize {
+        /// The bytes of the record that failed to deserialize.
+        bytes: Vec<u8>,
+        /// The error from deserializing the record.
+        #[source]
+        inner: serde_json::Error,
+    },
+}
+
+/// A message of a length-prefixed stream, being read by a
+/// [`LengthPrefixedStream`], could not be received or decoded. The type
+/// parameter is the error of the function that decodes each message.
+///
+/// [`LengthPrefixedStream`]: crate::endpoints::LengthPrefixedStream
+#[derive(Debug, thiserror::Error)]
+pub enum LengthPrefixedError<E> {
+    /// Reading from the body failed. The stream ends after yielding this.
+    #[error("failed to read a length-prefixed stream")]
+    Read(#[from] std::io::Error),
+    /// The body ended in the middle of a message, or of its prefix. This
+    /// contains the bytes of it that were received, including the prefix. The
+    /// stream ends after yielding this.
+    #[error("length-prefixed stream ended in the middle of a message")]
+    Truncated {
+        /// The bytes of the incomplete message.
+        bytes: Vec<u8>,
+    },
+    /// The prefix declared a message longer than the maximum that the stream
+    /// was given, so it was not received. The stream ends after yielding this,
+    /// because the messages that follow can't be found without reading past
+    /// it.
+    #[error("length-prefixed message of {length} bytes exceeds the maximum of {max_length}")]
+    TooLong {
+        /// The length that the prefix declared.
+        length: u64,
+        /// The maximum length that the stream accepts.
+        max_length: usize,
+    },
+    /// A whole message was received, but it failed to decode. This contains
+    /// the bytes of the message, without the prefix. The stream continues
+    /// with the next message.
+    #[error("failed to decode a message of a length-prefixed stream")]
+    Decode {
+        /// The bytes of the message that failed to decode.
+        bytes: Vec<u8>,
+        /// The error from decoding the message.
+        #[source]
+        inner: E,
+    },
+}
+
+/// A single error type for endpoint functions, which any of the errors that
+/// the [`endpoint!`] macro can bubble convert into, for libraries that would
+/// rather not define an enum of their own. It is a box of the original error,
+/// which can be recovered with [`Self::downcast_ref`] or [`Self::downcast`].
+///
+/// It converts from every error type of this module that the macro uses, from
+/// the errors of the provided backends, and from any `Box<dyn Error + Send +
+/// Sync>`, which is how the error of another [`Backend`] can be given to it,
+/// by implementing `From` for that type. Both [`Display`] and
+/// [`Error::source`] are forwarded to the boxed error, so it is transparent
+/// when it is printed or its sources are walked.
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+/// [`Backend`]: crate::endpoints::Backend
+/// [`Display`]: std::fmt::Display
+/// [`Error::source`]: std::error::Error::source
+#[derive(Debug)]
+pub struct BoxApiError(Box<dyn std::error::Error + Send + Sync + 'static>);
+
 macro_rules! impl_field_accessors {
-    ($implementor:ident) => {
-        impl $implementor {
+    ($implementor:ident $(<$generic:ident>)?) => {
+        impl $(<$generic>)? $implementor $(<$generic>)? {
             /// Reference to the URI of the request.
             pub fn uri(&self) -> &url::Url {
                 &self.uri
@@ -50,27 +274,49 @@ macro_rules! impl_field_accessors {
             pub fn into_uri_bytes(self) -> (url::Url, Vec<u8>) {
                 (self.uri, self.bytes)
             }
+
+            /// Reference to the ID that was sent with the request in the
+            /// `X-Request-Id` header, if the endpoint was given one.
+            pub fn request_id(&self) -> Option<&str> {
+                self.request_id.as_deref()
+            }
+
+            #[doc(hidden)]
+            pub fn __with_request_id(mut self, request_id: Option<String>) -> Self {
+                self.request_id = request_id;
+                self
+            }
         }
     };
 }
 
-impl_field_accessors!(DeserializeError);
+impl_field_accessors!(DeserializeError<E>);
 impl_field_accessors!(ResponseError);
+impl_field_accessors!(BodyReadError);
+#[cfg(feature = "jsonschema")]
+impl_field_accessors!(SchemaError);
 
-impl DeserializeError {
+impl<E> DeserializeError<E> {
     #[doc(hidden)]
-    pub fn __new(
-        uri: url::Url,
-        bytes: Vec<u8>,
-        error: serde_path_to_error::Error<serde_json::Error>,
-    ) -> Self {
+    pub fn __new(uri: url::Url, bytes: Vec<u8>, error: serde_path_to_error::Error<E>) -> Self {
         Self {
             uri,
             bytes,
+            request_id: None,
+            encoded: false,
             inner: error,
         }
     }
 
+    #[doc(hidden)]
+    pub fn __with_encoded_bytes(mut self, encoded: Option<Vec<u8>>) -> Self {
+        if let Some(encoded) = encoded {
+            self.bytes = encoded;
+            self.encoded = true;
+        }
+        self
+    }
+
     /// Reference to the [`Path`] of the value that failed to deserialize.
     ///
     /// [`Path`]: serde_path_to_error::Path
@@ -78,25 +324,671 @@ impl DeserializeError {
         self.inner.path()
     }
 
-    /// Reference to the original [`serde_json::Error`].
-    pub fn inner(&self) -> &serde_json::Error {
+    /// Reference to the original error from the deserializer, such as a
+    /// [`serde_json::Error`].
+    pub fn inner(&self) -> &E {
         self.inner.inner()
     }
 
-    /// Consume this error, taking out the original [`serde_json::Error`].
-    pub fn into_inner(self) -> serde_json::Error {
+    /// Consume this error, taking out the original error from the
+    /// deserializer, such as a [`serde_json::Error`].
+    pub fn into_inner(self) -> E {
         self.inner.into_inner()
     }
 }
 
+impl DeserializeError<serde_json::Error> {
+    /// The line of the body that the error occurred on, starting at 1. This
+    /// is zero if the error has no position, as with [`serde_json::Error`].
+    pub fn line(&self) -> usize {
+        self.inner().line()
+    }
+
+    /// The column of the line that the error occurred at, as a number of
+    /// bytes, starting at 1. This is zero if the error has no position, or if
+    /// it occurred immediately after a line feed, as with
+    /// [`serde_json::Error`].
+    pub fn column(&self) -> usize {
+        self.inner().column()
+    }
+
+    /// The bytes of the body within `radius` bytes on either side of where the
+    /// error occurred, for logging. Any bytes that are not valid UTF-8, such
+    /// as where the snippet splits a character, are replaced with
+    /// [`char::REPLACEMENT_CHARACTER`].
+    ///
+    /// This is `None` if the error has no position, or if the body was
+    /// decompressed before it was deserialized, because then the position is
+    /// not in [`Self::bytes`], which are as they were received.
+    pub fn snippet(&self, radius: usize) -> Option<String> {
+        let offset = self.offset()?;
+        let start = offset.saturating_sub(radius);
+        let end = offset.saturating_add(radius).min(self.bytes.len());
+
+        Some(String::from_utf8_lossy(&self.bytes[start..end]).into_owned())
+    }
+
+    /// The index of the byte in the body that the error occurred at, clamped
+    /// to the length of the body.
+    fn offset(&self) -> Option<usize> {
+        if self.encoded || self.line() == 0 {
+            return None;
+        }
+
+        // Skip past the line feed that ends each line before the one with the
+        // error. The column is counted from 1, but the error may be reported
+        // at column 0 right after a line feed.
+        let line_start = match self.line() {
+            1 => 0,
+            line => {
+                self.bytes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &byte)| byte == b'\n')
+                    .nth(line - 2)?
+                    .0
+                    + 1
+            }
+        };
+
+        Some((line_start + self.column().saturating_sub(1)).min(self.bytes.len()))
+    }
+}
+
+impl BodyReadError {
+    #[doc(hidden)]
+    pub fn __new(uri: url::Url, bytes: Vec<u8>, error: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        let kind = match error.kind() {
+            ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe => {
+                BodyReadErrorKind::ConnectionReset
+            }
+            ErrorKind::UnexpectedEof => BodyReadErrorKind::UnexpectedEof,
+            _ => BodyReadErrorKind::Other,
+        };
+
+        Self {
+            uri,
+            bytes,
+            request_id: None,
+            kind,
+            inner: error,
+        }
+    }
+
+    /// Copy of the reason that reading the body failed.
+    pub fn kind(&self) -> BodyReadErrorKind {
+        self.kind
+    }
+
+    /// Reference to the original I/O error from the body reader.
+    pub fn inner(&self) -> &std::io::Error {
+        &self.inner
+    }
+
+    /// Consume this error, taking out the original I/O error from the body
+    /// reader.
+    pub fn into_inner(self) -> std::io::Error {
+        self.inner
+    }
+}
+
+impl<E> RequestError<E> {
+    #[doc(hidden)]
+    pub fn __new(uri: url::Url, kind: RequestErrorKind, error: E) -> Self {
+        Self {
+            uri,
+            request_id: None,
+            kind,
+            inner: error,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn __with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// Reference to the URI that the request was being sent to.
+    pub fn uri(&self) -> &url::Url {
+        &self.uri
+    }
+
+    /// The ID that was sent in the `X-Request-Id` header of the request, if
+    /// the endpoint was given one.
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// Copy of the reason that sending the request failed.
+    pub fn kind(&self) -> RequestErrorKind {
+        self.kind
+    }
+
+    /// Reference to the original error from the backend.
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Consume this error, taking out the original error from the backend.
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+}
+
+impl RequestTimeoutError {
+    #[doc(hidden)]
+    pub fn __new(uri: url::Url, timeout: std::time::Duration) -> Self {
+        Self {
+            uri,
+            request_id: None,
+            timeout,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn __with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// Reference to the URI that the request was sent to.
+    pub fn uri(&self) -> &url::Url {
+        &self.uri
+    }
+
+    /// The ID that was sent in the `X-Request-Id` header of the request, if
+    /// the endpoint was given one.
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// The duration that the request was given to complete.
+    pub fn timeout(&self) -> std::time::Duration {
+        self.timeout
+    }
+}
+
 impl ResponseError {
     #[doc(hidden)]
-    pub fn __new(uri: url::Url, bytes: Vec<u8>, status: http::StatusCode) -> Self {
-        Self { uri, bytes, status }
+    pub fn __new(
+        uri: url::Url,
+        bytes: Vec<u8>,
+        status: http::StatusCode,
+        headers: http::HeaderMap,
+    ) -> Self {
+        Self {
+            uri,
+            bytes,
+            request_id: None,
+            status,
+            headers,
+        }
     }
 
     /// Copy of the response's status code.
     pub fn status_code(&self) -> http::StatusCode {
         self.status
     }
+
+    /// Reference to the response's headers.
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+
+    /// How long the server asked to wait before the request is retried, from
+    /// the `Retry-After` header that usually accompanies a `429 Too Many
+    /// Requests` or `503 Service Unavailable` status. Both a number of seconds
+    /// and an HTTP-date are understood; a date is converted to the time left
+    /// until it, which is zero if it has already passed. This is `None` if the
+    /// header is absent or can't be parsed.
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        let value = self.headers.get(http::header::RETRY_AFTER)?.to_str().ok()?;
+        parse_retry_after(value, std::time::SystemTime::now())
+    }
+
+    /// Consume this error, reconstructing the response that it was made from,
+    /// with the same status, headers, and body. This is useful for a proxy,
+    /// which can forward the error to its own clients as it was received.
+    ///
+    /// Headers that describe how the body was transferred, such as
+    /// `Content-Length` and `Transfer-Encoding`, are kept as they were, even
+    /// though the body has already been decoded by the client. Remove them if
+    /// the response is going to be sent again.
+    pub fn into_http_response(self) -> http::Response<Vec<u8>> {
+        let mut response = http::Response::new(self.bytes);
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers;
+        response
+    }
+}
+
+impl BoxApiError {
+    /// Box any error.
+    pub fn new<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        Self(error.into())
+    }
+
+    /// Whether the boxed error is of the type `E`.
+    pub fn is<E>(&self) -> bool
+    where
+        E: std::error::Error + 'static,
+    {
+        self.0.is::<E>()
+    }
+
+    /// Reference to the boxed error, if it is of the type `E`.
+    pub fn downcast_ref<E>(&self) -> Option<&E>
+    where
+        E: std::error::Error + 'static,
+    {
+        self.0.downcast_ref()
+    }
+
+    /// Consume this error, taking out the boxed error if it is of the type
+    /// `E`, or giving this back if it is not.
+    pub fn downcast<E>(self) -> Result<Box<E>, Self>
+    where
+        E: std::error::Error + 'static,
+    {
+        self.0.downcast().map_err(Self)
+    }
+
+    /// Consume this error, taking out the boxed error.
+    pub fn into_inner(self) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self.0
+    }
+}
+
+impl std::fmt::Display for BoxApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for BoxApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync + 'static>> for BoxApiError {
+    fn from(other: Box<dyn std::error::Error + Send + Sync + 'static>) -> Self {
+        Self(other)
+    }
+}
+
+impl From<std::convert::Infallible> for BoxApiError {
+    fn from(other: std::convert::Infallible) -> Self {
+        match other {}
+    }
+}
+
+impl<E> From<DeserializeError<E>> for BoxApiError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn from(other: DeserializeError<E>) -> Self {
+        Self::new(other)
+    }
+}
+
+/// The error from the backend is boxed first, because not every backend's
+/// error implements [`std::error::Error`], so this is a
+/// `RequestError<BoxApiError>` when it is downcast.
+impl<E> From<RequestError<E>> for BoxApiError
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    fn from(other: RequestError<E>) -> Self {
+        Self::new(RequestError {
+            uri: other.uri,
+            request_id: other.request_id,
+            kind: other.kind,
+            inner: Self::new(other.inner),
+        })
+    }
+}
+
+impl<E> From<crate::endpoints::DryRunError<E>> for BoxApiError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn from(other: crate::endpoints::DryRunError<E>) -> Self {
+        Self::new(other)
+    }
+}
+
+macro_rules! impl_from_for_box_api_error {
+    ($($(#[$meta:meta])* $error:ty),+ $(,)?) => {
+        $(
+            $(#[$meta])*
+            impl From<$error> for BoxApiError {
+                fn from(other: $error) -> Self {
+                    Self::new(other)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_for_box_api_error!(
+    RequestTimeoutError,
+    ResponseError,
+    BodyReadError,
+    JsonPatchError,
+    FormError,
+    JsonSeqError,
+    #[cfg(feature = "jsonschema")]
+    SchemaError,
+    #[cfg(feature = "isahc")]
+    isahc::Error,
+    #[cfg(feature = "surf")]
+    surf::Error,
+);
+
+impl JsonPatchError {
+    #[doc(hidden)]
+    pub fn __new(uri: url::Url, bytes: Vec<u8>, reason: String) -> Self {
+        Self { uri, bytes, reason }
+    }
+
+    /// Reference to the URI of the request.
+    pub fn uri(&self) -> &url::Url {
+        &self.uri
+    }
+
+    /// Reference to the serialized body of the request.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Description of the first structural problem that was found.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl FormError {
+    #[doc(hidden)]
+    pub fn __new(uri: url::Url, inner: serde_urlencoded::ser::Error) -> Self {
+        Self { uri, inner }
+    }
+
+    /// Reference to the URI of the request.
+    pub fn uri(&self) -> &url::Url {
+        &self.uri
+    }
+
+    /// Reference to the original error from [`serde_urlencoded`].
+    pub fn inner(&self) -> &serde_urlencoded::ser::Error {
+        &self.inner
+    }
+}
+
+#[cfg(feature = "jsonschema")]
+impl SchemaError {
+    #[doc(hidden)]
+    pub fn __new(uri: url::Url, bytes: Vec<u8>, messages: Vec<String>) -> Self {
+        Self {
+            uri,
+            bytes,
+            request_id: None,
+            messages,
+        }
+    }
+
+    /// A description of each way in which the body violated the schema.
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::convert::Infallible;
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use async_trait::async_trait;
+    use futures_lite::io::AsyncRead;
+
+    use crate::endpoints::{
+        endpoint, ApiResponse, Backend, BodyReadError, BodyReadErrorKind, BoxApiError,
+        DeserializeError, RequestRecorder, ResponseError, TestError,
+    };
+
+    async fn get_status(
+        client: &RequestRecorder,
+        base: &url::Url,
+    ) -> Result<ApiResponse<()>, TestError> {
+        endpoint! {
+            client GET,
+            uri: base / "status",
+        }
+    }
+
+    #[test]
+    fn test_into_http_response() {
+        let recorder = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+
+        recorder.push_response(
+            http::Response::builder()
+                .status(503)
+                .header("Retry-After", "120")
+                .body(b"down for maintenance".to_vec())
+                .unwrap(),
+        );
+
+        let error = match futures_lite::future::block_on(get_status(&recorder, &base)) {
+            Err(TestError::Response(error)) => error,
+            _ => panic!("expected an unsuccessful status"),
+        };
+        let response = error.into_http_response();
+
+        assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()["Retry-After"], "120");
+        assert_eq!(response.body(), b"down for maintenance");
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let recorder = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+        let throttled = |retry_after: Option<&str>| {
+            let mut response = http::Response::builder().status(429);
+            if let Some(retry_after) = retry_after {
+                response = response.header("Retry-After", retry_after);
+            }
+            response.body(Vec::new()).unwrap()
+        };
+
+        recorder.push_response(throttled(Some("120")));
+        recorder.push_response(throttled(Some("Fri, 31 Dec 2100 23:59:59 GMT")));
+        recorder.push_response(throttled(Some("later")));
+        recorder.push_response(throttled(None));
+
+        let retry_after = || match futures_lite::future::block_on(get_status(&recorder, &base)) {
+            Err(TestError::Response(error)) => error.retry_after(),
+            _ => panic!("expected an unsuccessful status"),
+        };
+
+        assert_eq!(retry_after(), Some(std::time::Duration::from_secs(120)));
+        assert!(retry_after().unwrap() > std::time::Duration::from_secs(86400 * 365));
+        assert_eq!(retry_after(), None);
+        assert_eq!(retry_after(), None);
+    }
+
+    /// Responds with a body that fails with `kind` after the first chunk.
+    struct DroppedBackend {
+        kind: io::ErrorKind,
+    }
+
+    struct DroppedBody {
+        chunk: Option<&'static [u8]>,
+        kind: io::ErrorKind,
+    }
+
+    impl AsyncRead for DroppedBody {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _ctx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            match self.chunk.take() {
+                Some(chunk) => {
+                    buf[..chunk.len()].copy_from_slice(chunk);
+                    Poll::Ready(Ok(chunk.len()))
+                }
+                None => Poll::Ready(Err(self.kind.into())),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Backend for DroppedBackend {
+        type Error = Infallible;
+        type Body = DroppedBody;
+
+        async fn send_request(
+            &self,
+            _request: http::Request<Vec<u8>>,
+        ) -> Result<http::Response<Self::Body>, Self::Error> {
+            Ok(http::Response::new(DroppedBody {
+                chunk: Some(b"{\"id\":"),
+                kind: self.kind,
+            }))
+        }
+    }
+
+    async fn get_dropped(
+        client: &DroppedBackend,
+        base: &url::Url,
+    ) -> Result<ApiResponse<serde_json::Value>, TestError> {
+        endpoint! {
+            client GET,
+            uri: base / "dropped",
+            request_id: "abc-123",
+        }
+    }
+
+    #[test]
+    fn test_body_read_error() {
+        let base = url::Url::parse("https://example.com/").unwrap();
+        let cases = [
+            (
+                io::ErrorKind::ConnectionReset,
+                BodyReadErrorKind::ConnectionReset,
+            ),
+            (
+                io::ErrorKind::UnexpectedEof,
+                BodyReadErrorKind::UnexpectedEof,
+            ),
+            (io::ErrorKind::TimedOut, BodyReadErrorKind::Other),
+        ];
+
+        for (io_kind, kind) in cases {
+            let client = DroppedBackend { kind: io_kind };
+            let error = match futures_lite::future::block_on(get_dropped(&client, &base)) {
+                Err(TestError::BodyRead(error)) => error,
+                _ => panic!("expected the body to fail to be read"),
+            };
+
+            assert_eq!(error.kind(), kind);
+            assert_eq!(error.inner().kind(), io_kind);
+            assert_eq!(error.bytes(), b"{\"id\":");
+            assert_eq!(error.request_id(), Some("abc-123"));
+        }
+    }
+
+    async fn get_boxed(
+        client: &RequestRecorder,
+        base: &url::Url,
+    ) -> Result<ApiResponse<u32>, BoxApiError> {
+        endpoint! {
+            client GET,
+            uri: base / "boxed",
+        }
+    }
+
+    #[test]
+    fn test_box_api_error() {
+        use std::error::Error as _;
+
+        let recorder = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+
+        recorder.push_response(
+            http::Response::builder()
+                .status(404)
+                .body(Vec::new())
+                .unwrap(),
+        );
+        recorder.push_response(http::Response::new(b"\"many\"".to_vec()));
+
+        let error = futures_lite::future::block_on(get_boxed(&recorder, &base)).unwrap_err();
+        assert!(error.is::<ResponseError>());
+        assert_eq!(
+            error.downcast_ref::<ResponseError>().unwrap().status_code(),
+            http::StatusCode::NOT_FOUND
+        );
+        assert!(error.downcast_ref::<DeserializeError>().is_none());
+
+        // The source is that of the boxed error, rather than the boxed error
+        // itself, so that it is not printed twice.
+        let error = futures_lite::future::block_on(get_boxed(&recorder, &base)).unwrap_err();
+        assert!(error
+            .source()
+            .unwrap()
+            .is::<serde_path_to_error::Error<serde_json::Error>>());
+        let error = error.downcast::<DeserializeError>().unwrap();
+        assert_eq!(error.bytes(), b"\"many\"");
+
+        let error = BoxApiError::from(BodyReadError::__new(
+            base.clone(),
+            Vec::new(),
+            io::ErrorKind::ConnectionReset.into(),
+        ));
+        assert_eq!(
+            error.downcast_ref::<BodyReadError>().unwrap().kind(),
+            BodyReadErrorKind::ConnectionReset
+        );
+
+        let other: Box<dyn std::error::Error + Send + Sync> = "request failed".into();
+        let error = BoxApiError::from(other);
+        assert_eq!(error.to_string(), "request failed");
+        assert!(error.downcast::<ResponseError>().is_err());
+    }
+
+    #[test]
+    fn test_deserialize_error_position() {
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct User {
+            id: u32,
+            name: String,
+        }
+
+        let base = url::Url::parse("https://example.com/").unwrap();
+        let bytes = b"{\n  \"id\": 1,\n  \"name\": 2\n}".to_vec();
+        let error = {
+            let deserializer = &mut serde_json::Deserializer::from_slice(&bytes);
+            serde_path_to_error::deserialize::<_, User>(deserializer).unwrap_err()
+        };
+        let error = DeserializeError::__new(base, bytes, error);
+
+        assert_eq!((error.line(), error.column()), (3, 11));
+        assert_eq!(error.snippet(4).unwrap(), "e\": 2\n}");
+
+        // The position is not in the bytes as they were received.
+        let error = error.__with_encoded_bytes(Some(b"\x1f\x8b".to_vec()));
+        assert_eq!(error.snippet(4), None);
+    }
 }
@@ -0,0 +1,173 @@
+use serde::de::DeserializeOwned;
+
+/// A data format that the body of a response can be deserialized from, for the
+/// `format` of the [`endpoint!`] macro and [`ApiResponse::from_bytes_as`].
+///
+/// The deserializer for the format is expected to be wrapped with
+/// [`serde_path_to_error::deserialize`], so that the [`DeserializeError`]
+/// reports where in the body the failure happened, as it does for JSON. Most
+/// formats that are implemented with [`serde`] provide a `Deserializer` that
+/// can be used this way.
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+/// [`ApiResponse::from_bytes_as`]: crate::endpoints::ApiResponse::from_bytes_as
+/// [`DeserializeError`]: crate::endpoints::DeserializeError
+pub trait ResponseFormat {
+    /// The error of the deserializer for this format.
+    type Error;
+
+    /// Deserialize the body `bytes` of a response into `T`.
+    fn deserialize<T>(bytes: &[u8]) -> Result<T, serde_path_to_error::Error<Self::Error>>
+    where
+        T: DeserializeOwned;
+}
+
+/// The default [`ResponseFormat`], which deserializes bodies with
+/// [`serde_json`].
+///
+/// An empty body, such as that of a `204 No Content` response, is deserialized
+/// as if it were `null`. This makes it possible to use `()` or an [`Option`] as
+/// the type, for endpoints that may not return a body.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json;
+
+impl ResponseFormat for Json {
+    type Error = serde_json::Error;
+
+    fn deserialize<T>(bytes: &[u8]) -> Result<T, serde_path_to_error::Error<Self::Error>>
+    where
+        T: DeserializeOwned,
+    {
+        let input: &[u8] = match bytes {
+            [] => b"null",
+            bytes => bytes,
+        };
+        let deserializer = &mut serde_json::Deserializer::from_slice(input);
+
+        serde_path_to_error::deserialize(deserializer)
+    }
+}
+
+/// A [`ResponseFormat`] for JSON bodies that may contain bare `NaN`,
+/// `Infinity`, and `-Infinity` literals, which are not valid JSON, but are
+/// sent by some servers for floating-point fields anyway. The literals are
+/// quoted with [`quote_non_finite`] before the body is deserialized the same
+/// as with [`Json`], so the fields that may hold them have to accept the
+/// quoted strings, such as with the [`lenient_float`] module.
+///
+/// The bytes of a [`DeserializeError`] are those of the body as it was
+/// received, so the position of an error after a quoted literal is off by two
+/// bytes for each literal before it.
+///
+/// [`quote_non_finite`]: crate::serde_with::lenient_float::quote_non_finite
+/// [`lenient_float`]: crate::serde_with::lenient_float
+/// [`DeserializeError`]: crate::endpoints::DeserializeError
+#[cfg(feature = "serde-with-lenient-float")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LenientJson;
+
+#[cfg(feature = "serde-with-lenient-float")]
+impl ResponseFormat for LenientJson {
+    type Error = serde_json::Error;
+
+    fn deserialize<T>(bytes: &[u8]) -> Result<T, serde_path_to_error::Error<Self::Error>>
+    where
+        T: DeserializeOwned,
+    {
+        Json::deserialize(&crate::serde_with::lenient_float::quote_non_finite(bytes))
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::convert::Infallible;
+
+    use serde::de::DeserializeOwned;
+    use serde::Deserialize;
+
+    use super::ResponseFormat;
+    use crate::endpoints::{endpoint, ApiResponse, RequestRecorder, TestError};
+
+    /// Bodies of `application/x-www-form-urlencoded`.
+    struct Form;
+
+    impl ResponseFormat for Form {
+        type Error = serde_urlencoded::de::Error;
+
+        fn deserialize<T>(bytes: &[u8]) -> Result<T, serde_path_to_error::Error<Self::Error>>
+        where
+            T: DeserializeOwned,
+        {
+            let parser = url::form_urlencoded::parse(bytes);
+            let deserializer = serde_urlencoded::Deserializer::new(parser);
+
+            serde_path_to_error::deserialize(deserializer)
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Token {
+        access_token: String,
+        expires_in: u32,
+    }
+
+    async fn get_token(
+        client: &RequestRecorder,
+        base: &url::Url,
+    ) -> Result<ApiResponse<Token>, TestError<Infallible, serde_urlencoded::de::Error>> {
+        endpoint! {
+            client POST,
+            uri: base / "oauth/token",
+            format: Form,
+        }
+    }
+
+    #[test]
+    fn test_custom_format() {
+        let recorder = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+        let response = |body: &[u8]| http::Response::new(body.to_vec());
+
+        recorder.push_response(response(b"access_token=abc&expires_in=3600"));
+        recorder.push_response(response(b"access_token=abc&expires_in=soon"));
+
+        let token = futures_lite::future::block_on(get_token(&recorder, &base)).unwrap();
+        assert_eq!(
+            token.into_value(),
+            Token {
+                access_token: "abc".to_owned(),
+                expires_in: 3600,
+            }
+        );
+
+        match futures_lite::future::block_on(get_token(&recorder, &base)) {
+            Err(TestError::Deserialize(error)) => {
+                assert_eq!(error.uri().as_str(), "https://example.com/oauth/token");
+                assert_eq!(error.bytes(), b"access_token=abc&expires_in=soon");
+                assert_eq!(error.path().to_string(), "expires_in");
+            }
+            _ => panic!("expected the body to fail to deserialize"),
+        }
+    }
+
+    #[cfg(feature = "serde-with-lenient-float")]
+    #[test]
+    fn test_lenient_json() {
+        #[derive(Debug, Deserialize)]
+        struct Reading {
+            #[serde(with = "crate::serde_with::lenient_float")]
+            value: f64,
+        }
+
+        let uri = url::Url::parse("https://example.com/readings").unwrap();
+        let bytes = br#"[{"value":NaN},{"value":-Infinity},{"value":2.5}]"#.to_vec();
+        let readings =
+            ApiResponse::<Vec<Reading>>::from_bytes_as::<super::LenientJson>(&uri, bytes.clone())
+                .unwrap();
+
+        assert!(readings[0].value.is_nan());
+        assert_eq!(readings[1].value, f64::NEG_INFINITY);
+        assert_eq!(readings[2].value, 2.5);
+        assert_eq!(readings.bytes(), bytes.as_slice());
+    }
+}
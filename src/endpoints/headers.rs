@@ -0,0 +1,41 @@
+use std::fmt::Display;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use http::header::HeaderName;
+use http::request::Builder;
+
+/// Add each of the `defaults` to the request being built, unless a header with
+/// the same name has already been set on it. Invalid names or values are left
+/// for the builder to report, the same as every other header.
+#[doc(hidden)]
+pub fn __apply_default_headers<'a, K, V, I>(mut builder: Builder, defaults: I) -> Builder
+where
+    K: AsRef<str> + 'a,
+    V: AsRef<str> + 'a,
+    I: IntoIterator<Item = &'a (K, V)>,
+{
+    for (name, value) in defaults {
+        let (name, value) = (name.as_ref(), value.as_ref());
+        let present = match (builder.headers_ref(), HeaderName::try_from(name)) {
+            (Some(headers), Ok(name)) => headers.contains_key(name),
+            _ => false,
+        };
+
+        if !present {
+            builder = builder.header(name, value);
+        }
+    }
+
+    builder
+}
+
+/// The value of an `Authorization` header for the `Basic` scheme, with the
+/// `user` and `password` joined by a `:` and encoded with base-64.
+#[doc(hidden)]
+pub fn __basic_auth(user: &impl Display, password: &impl Display) -> String {
+    format!(
+        "Basic {}",
+        STANDARD.encode(format!("{}:{}", user, password))
+    )
+}
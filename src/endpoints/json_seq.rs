@@ -0,0 +1,245 @@
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_lite::io::AsyncRead;
+use futures_lite::Stream;
+use serde::de::DeserializeOwned;
+
+use crate::endpoints::errors::JsonSeqError;
+
+/// The byte that every record of a JSON text sequence begins with.
+const RECORD_SEPARATOR: u8 = 0x1E;
+
+/// The number of bytes to read from the body at once.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Reads a JSON text sequence (`application/json-seq`), as described by
+/// [RFC 7464], from the body of a response, and deserializes every record
+/// into `T` as soon as it has been received.
+///
+/// Each record is a JSON text that is preceded by an ASCII record separator
+/// (`0x1E`) and followed by a line feed. A record may be split across any
+/// number of reads from the body, and is yielded once it is complete, which
+/// is when it ends with a line feed and the bytes before it are a whole JSON
+/// text, or when the next separator or the end of the body is reached. Any
+/// bytes before the first separator are ignored.
+///
+/// A record that fails to deserialize is yielded as a
+/// [`JsonSeqError::Deserialize`], and the stream continues with the next
+/// record, as the RFC intends. If reading from the body fails, the error is
+/// yielded as a [`JsonSeqError::Read`], and the stream ends.
+///
+/// This is intended to be used with the [`StreamingApiResponse`] from an
+/// endpoint with `stream_body: true`, but any [`AsyncRead`] will do.
+///
+/// [RFC 7464]: https://www.rfc-editor.org/rfc/rfc7464
+/// [`StreamingApiResponse`]: crate::endpoints::StreamingApiResponse
+pub struct JsonSeqStream<R, T> {
+    reader: R,
+    buffer: Vec<u8>,
+    /// The number of bytes at the start of the buffer that have already been
+    /// searched for a line feed ending the current record.
+    scanned: usize,
+    eof: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<R, T> JsonSeqStream<R, T> {
+    /// Read the records of a JSON text sequence from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            scanned: 0,
+            eof: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Consume this stream, taking out the reader. Any bytes that have been
+    /// read from it, but not yet yielded as a record, are discarded.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R, T> JsonSeqStream<R, T>
+where
+    T: DeserializeOwned,
+{
+    /// Take the next complete record out of the buffer, if there is one.
+    fn next_record(&mut self) -> Option<Result<T, JsonSeqError>> {
+        loop {
+            // Skip ahead to the separator of the next record, discarding
+            // anything that came before it.
+            match self
+                .buffer
+                .iter()
+                .position(|&byte| byte == RECORD_SEPARATOR)
+            {
+                Some(start) => {
+                    self.buffer.drain(..start);
+                    self.scanned = self.scanned.saturating_sub(start).max(1);
+                }
+                None => {
+                    self.buffer.clear();
+                    self.scanned = 0;
+                    return None;
+                }
+            }
+
+            let next = self.buffer[1..]
+                .iter()
+                .position(|&byte| byte == RECORD_SEPARATOR)
+                .map(|index| index + 1);
+
+            let end = match next {
+                Some(end) => end,
+                None => match self.find_line_end() {
+                    Some(end) => end,
+                    None if self.eof => self.buffer.len(),
+                    None => return None,
+                },
+            };
+
+            let record = self.buffer.drain(..end).skip(1).collect::<Vec<_>>();
+            self.scanned = 0;
+
+            // Whitespace alone, such as from two separators in a row, is not a
+            // record; the RFC says that it should be skipped.
+            if record.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+
+            return Some(match serde_json::from_slice(&record) {
+                Ok(value) => Ok(value),
+                Err(inner) => Err(JsonSeqError::Deserialize {
+                    bytes: record,
+                    inner,
+                }),
+            });
+        }
+    }
+
+    /// Find the end of a record that is the first in the buffer, and that is
+    /// not followed by another separator yet. Because a JSON text may contain
+    /// line feeds between its tokens, the bytes before each line feed are
+    /// tried until they are a whole JSON text.
+    fn find_line_end(&mut self) -> Option<usize> {
+        while let Some(index) = self.buffer[self.scanned..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+        {
+            let end = self.scanned + index + 1;
+            self.scanned = end;
+
+            // Anything but running out of bytes means that the JSON text is
+            // whole, even if it is invalid, or doesn't deserialize into `T`.
+            match serde_json::from_slice::<serde::de::IgnoredAny>(&self.buffer[1..end]) {
+                Err(error) if error.is_eof() => continue,
+                _ => return Some(end),
+            }
+        }
+
+        None
+    }
+}
+
+impl<R, T> Stream for JsonSeqStream<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    type Item = Result<T, JsonSeqError>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(record) = this.next_record() {
+                return Poll::Ready(Some(record));
+            }
+
+            if this.eof {
+                return Poll::Ready(None);
+            }
+
+            let filled = this.buffer.len();
+            this.buffer.resize(filled + CHUNK_SIZE, 0);
+
+            let result = Pin::new(&mut this.reader).poll_read(ctx, &mut this.buffer[filled..]);
+            let read = match &result {
+                Poll::Ready(Ok(read)) => *read,
+                _ => 0,
+            };
+            this.buffer.truncate(filled + read);
+
+            match result {
+                Poll::Ready(Ok(0)) => this.eof = true,
+                Poll::Ready(Ok(_)) => (),
+                Poll::Ready(Err(error)) => {
+                    this.eof = true;
+                    this.buffer.clear();
+                    return Poll::Ready(Some(Err(error.into())));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::StreamExt;
+    use serde::Deserialize;
+
+    use super::JsonSeqStream;
+    use crate::endpoints::tests::ChunkedReader;
+    use crate::endpoints::JsonSeqError;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Event {
+        id: u32,
+    }
+
+    fn collect(chunks: &[&[u8]]) -> Vec<Result<Event, JsonSeqError>> {
+        let reader = ChunkedReader::new(chunks);
+        futures_lite::future::block_on(JsonSeqStream::new(reader).collect())
+    }
+
+    #[test]
+    fn test_records_across_chunks() {
+        let results = collect(&[
+            b"\x1e{\"id\":1}\n\x1e{\"i",
+            b"d\":2}\n",
+            b"\x1e{\n  \"id\": 3\n}\n\x1e\n\x1e{\"id\":4}",
+        ]);
+        let events = results.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+
+        assert_eq!(
+            events,
+            [
+                Event { id: 1 },
+                Event { id: 2 },
+                Event { id: 3 },
+                Event { id: 4 }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_record() {
+        let results = collect(&[b"\x1e{\"id\":1}\n\x1e{\"id\":\"two\"}\n\x1e{\"id\":3}\n"]);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &Event { id: 1 });
+        match &results[1] {
+            Err(JsonSeqError::Deserialize { bytes, .. }) => {
+                assert_eq!(bytes, b"{\"id\":\"two\"}\n")
+            }
+            _ => panic!("expected the second record to fail to deserialize"),
+        }
+        assert_eq!(results[2].as_ref().unwrap(), &Event { id: 3 });
+    }
+}
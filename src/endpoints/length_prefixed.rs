@@ -0,0 +1,341 @@
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_lite::io::AsyncRead;
+use futures_lite::Stream;
+
+use crate::endpoints::errors::LengthPrefixedError;
+
+/// The number of bytes to read from the body at once.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// The longest message that is accepted unless another maximum is given, which
+/// is the same as the default of gRPC.
+const DEFAULT_MAX_LENGTH: usize = 4 * 1024 * 1024;
+
+/// The order of the bytes of a [`LengthPrefix`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// The most significant byte comes first, which is also known as network
+    /// byte order.
+    Big,
+    /// The least significant byte comes first.
+    Little,
+}
+
+/// The format of the length that precedes every message of a
+/// [`LengthPrefixedStream`]: an unsigned integer of `width` bytes, counting
+/// the bytes of the message after it, without itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthPrefix {
+    width: usize,
+    endianness: Endianness,
+}
+
+impl LengthPrefix {
+    /// A prefix of `width` bytes, in the order of `endianness`.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is zero, or greater than eight, which is the most that fits
+    /// in a `u64`.
+    pub fn new(width: usize, endianness: Endianness) -> Self {
+        assert!(
+            (1..=8).contains(&width),
+            "the width of a length prefix must be between one and eight bytes"
+        );
+
+        Self { width, endianness }
+    }
+
+    /// The number of bytes of the prefix.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The order of the bytes of the prefix.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Read the length from the first `width` bytes of `bytes`, which must
+    /// have at least that many.
+    fn read(&self, bytes: &[u8]) -> u64 {
+        let bytes = &bytes[..self.width];
+        let fold = |length: u64, &byte: &u8| length << 8 | u64::from(byte);
+
+        match self.endianness {
+            Endianness::Big => bytes.iter().fold(0, fold),
+            Endianness::Little => bytes.iter().rev().fold(0, fold),
+        }
+    }
+}
+
+impl Default for LengthPrefix {
+    /// A prefix of four bytes, in big-endian order, as used by most protocols,
+    /// including gRPC.
+    fn default() -> Self {
+        Self::new(4, Endianness::Big)
+    }
+}
+
+/// Reads messages that are each preceded by their length from the body of a
+/// response, and decodes every message into `T` with `F` as soon as it has
+/// been received.
+///
+/// The length is an unsigned integer, which is four bytes in big-endian order
+/// unless another [`LengthPrefix`] is given with [`Self::with_prefix`]. A
+/// message and its prefix may be split across any number of reads from the
+/// body. Nothing other than the length is expected between messages, so a
+/// framing with more in its header, such as the flags byte of gRPC, has to be
+/// read with a wider prefix, or handled by the decoder.
+///
+/// A message that fails to decode is yielded as a
+/// [`LengthPrefixedError::Decode`], and the stream continues with the next
+/// message. If reading from the body fails, the error is yielded as a
+/// [`LengthPrefixedError::Read`], and if the body ends in the middle of a
+/// message, the bytes of it are yielded as a
+/// [`LengthPrefixedError::Truncated`]; the stream ends after either.
+///
+/// Every message is buffered until it has been received whole, so a prefix
+/// that declares a message longer than 4 MiB, or the maximum given with
+/// [`Self::with_max_length`], is yielded as a
+/// [`LengthPrefixedError::TooLong`], and the stream ends, rather than letting
+/// the server decide how much memory is used.
+///
+/// This is intended to be used with the [`StreamingApiResponse`] from an
+/// endpoint with `stream_body: true`, but any [`AsyncRead`] will do.
+///
+/// [`StreamingApiResponse`]: crate::endpoints::StreamingApiResponse
+pub struct LengthPrefixedStream<R, T, F> {
+    reader: R,
+    decode: F,
+    prefix: LengthPrefix,
+    max_length: usize,
+    buffer: Vec<u8>,
+    eof: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<R, T, F> LengthPrefixedStream<R, T, F> {
+    /// Read the messages from `reader`, decoding each with `decode`.
+    pub fn new(reader: R, decode: F) -> Self {
+        Self {
+            reader,
+            decode,
+            prefix: LengthPrefix::default(),
+            max_length: DEFAULT_MAX_LENGTH,
+            buffer: Vec::new(),
+            eof: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Read the length of each message with `prefix`, rather than the default
+    /// of four bytes in big-endian order.
+    pub fn with_prefix(mut self, prefix: LengthPrefix) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Accept messages of up to `max_length` bytes, rather than the default of
+    /// 4 MiB.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Consume this stream, taking out the reader. Any bytes that have been
+    /// read from it, but not yet yielded as a message, are discarded.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R, T, F, E> LengthPrefixedStream<R, T, F>
+where
+    F: FnMut(&[u8]) -> Result<T, E>,
+{
+    /// Take the next complete message out of the buffer, if there is one.
+    fn next_message(&mut self) -> Option<Result<T, LengthPrefixedError<E>>> {
+        let width = self.prefix.width;
+
+        if self.buffer.len() < width {
+            return None;
+        }
+
+        let length = self.prefix.read(&self.buffer);
+        let length = match usize::try_from(length) {
+            Ok(length) if length <= self.max_length => length,
+            // The messages that follow can't be found without receiving this
+            // one, so the stream ends here.
+            _ => {
+                self.eof = true;
+                self.buffer.clear();
+                return Some(Err(LengthPrefixedError::TooLong {
+                    length,
+                    max_length: self.max_length,
+                }));
+            }
+        };
+
+        if self.buffer.len() - width < length {
+            return None;
+        }
+
+        let bytes = self
+            .buffer
+            .drain(..width + length)
+            .skip(width)
+            .collect::<Vec<_>>();
+
+        Some(match (self.decode)(&bytes) {
+            Ok(message) => Ok(message),
+            Err(inner) => Err(LengthPrefixedError::Decode { bytes, inner }),
+        })
+    }
+}
+
+impl<R, T, F, E> Stream for LengthPrefixedStream<R, T, F>
+where
+    R: AsyncRead + Unpin,
+    F: FnMut(&[u8]) -> Result<T, E> + Unpin,
+{
+    type Item = Result<T, LengthPrefixedError<E>>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(message) = this.next_message() {
+                return Poll::Ready(Some(message));
+            }
+
+            if this.eof {
+                if this.buffer.is_empty() {
+                    return Poll::Ready(None);
+                }
+
+                let bytes = std::mem::take(&mut this.buffer);
+                return Poll::Ready(Some(Err(LengthPrefixedError::Truncated { bytes })));
+            }
+
+            let filled = this.buffer.len();
+            this.buffer.resize(filled + CHUNK_SIZE, 0);
+
+            let result = Pin::new(&mut this.reader).poll_read(ctx, &mut this.buffer[filled..]);
+            let read = match &result {
+                Poll::Ready(Ok(read)) => *read,
+                _ => 0,
+            };
+            this.buffer.truncate(filled + read);
+
+            match result {
+                Poll::Ready(Ok(0)) => this.eof = true,
+                Poll::Ready(Ok(_)) => (),
+                Poll::Ready(Err(error)) => {
+                    this.eof = true;
+                    this.buffer.clear();
+                    return Poll::Ready(Some(Err(error.into())));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::Utf8Error;
+
+    use futures_lite::StreamExt;
+
+    use super::{Endianness, LengthPrefix, LengthPrefixedStream};
+    use crate::endpoints::tests::ChunkedReader;
+    use crate::endpoints::LengthPrefixedError;
+
+    fn collect(
+        chunks: &[&[u8]],
+        prefix: LengthPrefix,
+    ) -> Vec<Result<String, LengthPrefixedError<Utf8Error>>> {
+        let reader = ChunkedReader::new(chunks);
+        let decode = |bytes: &[u8]| std::str::from_utf8(bytes).map(String::from);
+        let stream = LengthPrefixedStream::new(reader, decode).with_prefix(prefix);
+
+        futures_lite::future::block_on(stream.collect())
+    }
+
+    #[test]
+    fn test_messages_across_chunks() {
+        let results = collect(
+            &[
+                b"\x00\x00\x00\x05hello\x00\x00",
+                b"\x00\x05wor",
+                b"ld\x00\x00\x00\x00",
+            ],
+            LengthPrefix::default(),
+        );
+        let messages = results.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+
+        assert_eq!(messages, ["hello", "world", ""]);
+    }
+
+    #[test]
+    fn test_prefix_width_and_endianness() {
+        let prefix = LengthPrefix::new(2, Endianness::Little);
+        let results = collect(&[b"\x02\x00hi\x03", b"\x00hey"], prefix);
+        let messages = results.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+
+        assert_eq!(messages, ["hi", "hey"]);
+    }
+
+    #[test]
+    fn test_invalid_and_truncated_messages() {
+        let results = collect(
+            &[b"\x00\x00\x00\x01\xff\x00\x00\x00\x02ok\x00\x00\x00\x04ab"],
+            LengthPrefix::default(),
+        );
+
+        assert_eq!(results.len(), 3);
+        match &results[0] {
+            Err(LengthPrefixedError::Decode { bytes, .. }) => assert_eq!(bytes, b"\xff"),
+            _ => panic!("expected the first message to fail to decode"),
+        }
+        assert_eq!(results[1].as_ref().unwrap(), "ok");
+        match &results[2] {
+            Err(LengthPrefixedError::Truncated { bytes }) => {
+                assert_eq!(bytes, b"\x00\x00\x00\x04ab")
+            }
+            _ => panic!("expected the last message to be truncated"),
+        }
+    }
+
+    #[test]
+    fn test_message_too_long() {
+        let reader =
+            ChunkedReader::new(&[b"\x00\x00\x00\x02ok\x00\x00\x00\x05hello\x00\x00\x00\x02hi"]);
+        let decode = |bytes: &[u8]| std::str::from_utf8(bytes).map(String::from);
+        let stream = LengthPrefixedStream::new(reader, decode).with_max_length(4);
+        let results = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap(), "ok");
+        match &results[1] {
+            Err(LengthPrefixedError::TooLong { length, max_length }) => {
+                assert_eq!((*length, *max_length), (5, 4))
+            }
+            _ => panic!("expected the second message to be too long"),
+        }
+
+        // The default maximum stops a hostile prefix before the body is read.
+        let results = collect(&[b"\xff\xff\xff\xff"], LengthPrefix::default());
+        assert!(matches!(
+            results[..],
+            [Err(LengthPrefixedError::TooLong {
+                length: 0xffff_ffff,
+                ..
+            })]
+        ));
+    }
+}
@@ -4,10 +4,81 @@
 //!
 //! [`endpoint!`]: crate::endpoints::endpoint
 
+pub(crate) mod backend;
+pub(crate) mod collect;
+#[cfg(feature = "curl")]
+pub(crate) mod curl;
+pub(crate) mod dry_run;
+pub(crate) mod encoding;
 pub(crate) mod errors;
+pub(crate) mod format;
+pub(crate) mod headers;
+pub(crate) mod json_seq;
+pub(crate) mod length_prefixed;
 pub(crate) mod macros;
+pub(crate) mod patch;
+pub(crate) mod query;
+pub(crate) mod range;
+pub(crate) mod rate_limit;
 pub(crate) mod response;
+pub(crate) mod retry;
+pub(crate) mod retry_after;
+#[cfg(feature = "jsonschema")]
+pub(crate) mod schema;
+#[cfg(feature = "testing")]
+pub(crate) mod testing;
+#[cfg(feature = "timings")]
+pub(crate) mod timings;
 
+pub use backend::*;
+pub use collect::*;
+#[cfg(feature = "curl")]
+pub use curl::*;
+pub use dry_run::*;
 pub use errors::*;
+pub use format::*;
+pub use json_seq::*;
+pub use length_prefixed::*;
 pub use macros::*;
+pub use patch::{JSON_PATCH_CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE};
+pub use range::*;
+pub use rate_limit::*;
 pub use response::*;
+pub use retry::*;
+pub use retry_after::*;
+#[cfg(feature = "testing")]
+pub use testing::*;
+#[cfg(feature = "timings")]
+pub use timings::*;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures_lite::io::AsyncRead;
+
+    /// Returns each of its chunks from a separate read, for testing that a
+    /// body is decoded the same no matter where it is split.
+    pub(super) struct ChunkedReader(VecDeque<Vec<u8>>);
+
+    impl ChunkedReader {
+        pub fn new(chunks: &[&[u8]]) -> Self {
+            Self(chunks.iter().map(|chunk| chunk.to_vec()).collect())
+        }
+    }
+
+    impl AsyncRead for ChunkedReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _ctx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let chunk = self.0.pop_front().unwrap_or_default();
+            buf[..chunk.len()].copy_from_slice(&chunk);
+            Poll::Ready(Ok(chunk.len()))
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use serde_json::Value;
+
+use crate::endpoints::errors::JsonPatchError;
+
+/// Media type of a JSON Merge Patch document, as described by [RFC 7396].
+///
+/// [RFC 7396]: https://www.rfc-editor.org/rfc/rfc7396
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+
+/// Media type of a JSON Patch document, as described by [RFC 6902].
+///
+/// [RFC 6902]: https://www.rfc-editor.org/rfc/rfc6902
+pub const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
+
+/// Check that `body` is structurally a JSON Patch document: an array of
+/// operation objects, each with a known `op`, a `path`, and the extra member
+/// that its operation requires. The values of `path` and `from` are not
+/// checked to be valid JSON Pointers.
+#[doc(hidden)]
+#[allow(clippy::result_large_err)]
+pub fn __validate_json_patch(uri: &url::Url, body: &Value) -> Result<(), JsonPatchError> {
+    let reject = |reason: String| {
+        // Use of unwrap:
+        // The value was already produced by `serde_json`, so it must be
+        // possible to serialize it back into a string.
+        let bytes = serde_json::to_vec(body).unwrap();
+        JsonPatchError::__new(uri.clone(), bytes, reason)
+    };
+
+    let operations = match body {
+        Value::Array(operations) => operations,
+        _ => return Err(reject("expected an array of operations".to_owned())),
+    };
+
+    for (index, operation) in operations.iter().enumerate() {
+        let operation = match operation {
+            Value::Object(operation) => operation,
+            _ => return Err(reject(format!("operation {} is not an object", index))),
+        };
+
+        if !matches!(operation.get("path"), Some(Value::String(_))) {
+            return Err(reject(format!(
+                "operation {} is missing a string `path`",
+                index
+            )));
+        }
+
+        let required = match operation.get("op").and_then(Value::as_str) {
+            Some("add" | "replace" | "test") => Some("value"),
+            Some("move" | "copy") => Some("from"),
+            Some("remove") => None,
+            Some(op) => {
+                return Err(reject(format!(
+                    "operation {} has an unknown `op` {:?}",
+                    index, op
+                )))
+            }
+            None => {
+                return Err(reject(format!(
+                    "operation {} is missing a string `op`",
+                    index
+                )))
+            }
+        };
+
+        match required {
+            Some("from") if !matches!(operation.get("from"), Some(Value::String(_))) => {
+                return Err(reject(format!(
+                    "operation {} is missing a string `from`",
+                    index
+                )));
+            }
+            Some(member) if !operation.contains_key(member) => {
+                return Err(reject(format!(
+                    "operation {} is missing `{}`",
+                    index, member
+                )));
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use serde::Serialize;
+    use serde_json::json;
+
+    use super::{JSON_PATCH_CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE};
+    use crate::endpoints::{endpoint, ApiResponse, RequestRecorder, TestError};
+
+    #[derive(Serialize)]
+    struct UserChanges {
+        nickname: Option<String>,
+    }
+
+    async fn merge_user(
+        client: &RequestRecorder,
+        base: &url::Url,
+        changes: &UserChanges,
+    ) -> Result<ApiResponse<()>, TestError> {
+        endpoint! {
+            client PATCH,
+            uri: base / "user",
+            patch_type: merge,
+            body: changes,
+        }
+    }
+
+    async fn json_patch_user(
+        client: &RequestRecorder,
+        base: &url::Url,
+        operations: &serde_json::Value,
+    ) -> Result<ApiResponse<()>, TestError> {
+        endpoint! {
+            client PATCH,
+            uri: base / "user",
+            patch_type: json_patch,
+            body: operations,
+        }
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let recorder = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+        let changes = UserChanges { nickname: None };
+
+        futures_lite::future::block_on(merge_user(&recorder, &base, &changes)).unwrap();
+
+        let request = &recorder.requests()[0];
+        assert_eq!(request.method, http::Method::PATCH);
+        assert_eq!(
+            request.headers[http::header::CONTENT_TYPE],
+            MERGE_PATCH_CONTENT_TYPE
+        );
+        assert_eq!(request.body, br#"{"nickname":null}"#);
+    }
+
+    #[test]
+    fn test_json_patch() {
+        let recorder = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+        let operations = json!([
+            { "op": "replace", "path": "/nickname", "value": "spike" },
+            { "op": "move", "from": "/email", "path": "/backup_email" },
+            { "op": "remove", "path": "/avatar" },
+        ]);
+
+        futures_lite::future::block_on(json_patch_user(&recorder, &base, &operations)).unwrap();
+
+        let request = &recorder.requests()[0];
+        assert_eq!(
+            request.headers[http::header::CONTENT_TYPE],
+            JSON_PATCH_CONTENT_TYPE
+        );
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&request.body).unwrap(),
+            operations
+        );
+    }
+
+    #[test]
+    fn test_json_patch_invalid() {
+        let recorder = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+
+        let invalid = [
+            json!({ "op": "remove", "path": "/avatar" }),
+            json!([{ "op": "add", "path": "/nickname" }]),
+            json!([{ "op": "copy", "path": "/nickname", "from": 1 }]),
+            json!([{ "op": "rename", "path": "/nickname" }]),
+        ];
+
+        for operations in invalid {
+            let result =
+                futures_lite::future::block_on(json_patch_user(&recorder, &base, &operations));
+
+            match result {
+                Err(TestError::JsonPatch(error)) => {
+                    assert_eq!(error.uri(), &base.join("user").unwrap())
+                }
+                _ => panic!("expected the body to be rejected"),
+            }
+        }
+
+        // None of the invalid documents should have been sent.
+        assert!(recorder.requests().is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+use serde::Serialize;
+
+/// Serialize `params` as a query string with [`serde_qs::to_string`], and
+/// then remove the index from the key of every element of a sequence, so that
+/// `ids[0]=1&ids[1]=2` becomes `ids=1&ids=2`. Any other brackets, such as
+/// those of a nested structure, are left as they are.
+#[doc(hidden)]
+pub fn __repeated_query<T>(params: &T) -> Result<String, serde_qs::Error>
+where
+    T: Serialize,
+{
+    let query = serde_qs::to_string(params)?;
+    let pairs = query.split('&').map(|pair| {
+        // Brackets in a value are always percent-encoded, and so are any in
+        // the name of a field, so every bracket in a key was put there by the
+        // serializer.
+        let (key, value) = pair.split_at(pair.find('=').unwrap_or(pair.len()));
+        let mut stripped = String::with_capacity(pair.len());
+        let mut rest = key;
+
+        while let Some(start) = rest.find('[') {
+            let (before, bracketed) = rest.split_at(start);
+            stripped.push_str(before);
+
+            match bracketed.find(']') {
+                Some(end) if end > 1 && bracketed[1..end].bytes().all(|b| b.is_ascii_digit()) => {
+                    rest = &bracketed[end + 1..];
+                }
+                _ => {
+                    stripped.push('[');
+                    rest = &bracketed[1..];
+                }
+            }
+        }
+
+        stripped.push_str(rest);
+        stripped.push_str(value);
+        stripped
+    });
+
+    Ok(pairs.collect::<Vec<_>>().join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::__repeated_query;
+
+    #[test]
+    fn test_repeated_query() {
+        #[derive(Serialize)]
+        struct Filter {
+            ids: Vec<u32>,
+            name: &'static str,
+            sort: Sort,
+        }
+
+        #[derive(Serialize)]
+        struct Sort {
+            fields: Vec<&'static str>,
+        }
+
+        let query = __repeated_query(&Filter {
+            ids: vec![1, 2],
+            name: "[0]",
+            sort: Sort {
+                fields: vec!["name", "id"],
+            },
+        })
+        .unwrap();
+
+        assert_eq!(
+            query,
+            "ids=1&ids=2&name=%5B0%5D&sort[fields]=name&sort[fields]=id"
+        );
+    }
+}
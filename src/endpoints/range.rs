@@ -0,0 +1,149 @@
+/// The value of a `Content-Range` header in bytes, which says which part of
+/// a resource the body of a `206 Partial Content` response is, or, for a
+/// `416 Range Not Satisfiable` response, how large the resource is. This is
+/// what is needed to resume a download, or to split one into several
+/// requests.
+///
+/// Get this from a response with [`ApiResponse::content_range`] or
+/// [`StreamingApiResponse::content_range`], or parse it with
+/// [`ContentRange::parse`].
+///
+/// [`ApiResponse::content_range`]: crate::endpoints::ApiResponse::content_range
+/// [`StreamingApiResponse::content_range`]: crate::endpoints::StreamingApiResponse::content_range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    range: Option<(u64, u64)>,
+    total: Option<u64>,
+}
+
+impl ContentRange {
+    /// Parse the value of a `Content-Range` header, either in the form
+    /// `bytes 200-1000/67589`, where the total may be `*` if it is unknown,
+    /// or in the form `bytes */67589` for a range that was not satisfiable.
+    /// `None` if the unit is not `bytes`, or the value is malformed, such as a
+    /// range that ends before it starts, or that ends past the total.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (unit, rest) = value.trim().split_once(' ')?;
+        if !unit.eq_ignore_ascii_case("bytes") {
+            return None;
+        }
+
+        let (range, total) = rest.trim_start().split_once('/')?;
+        let total = match total {
+            "*" => None,
+            total => Some(parse_position(total)?),
+        };
+
+        let range = match range {
+            "*" => {
+                // The total is required when the range is unsatisfiable.
+                total?;
+                None
+            }
+            range => {
+                let (start, end) = range.split_once('-')?;
+                let (start, end) = (parse_position(start)?, parse_position(end)?);
+
+                if end < start || total.is_some_and(|total| end >= total) {
+                    return None;
+                }
+
+                Some((start, end))
+            }
+        };
+
+        Some(Self { range, total })
+    }
+
+    /// The position of the first byte of the body in the resource, or `None`
+    /// if the range was not satisfiable.
+    pub fn start(&self) -> Option<u64> {
+        self.range.map(|(start, _)| start)
+    }
+
+    /// The position of the last byte of the body in the resource, which is
+    /// inclusive, or `None` if the range was not satisfiable.
+    pub fn end(&self) -> Option<u64> {
+        self.range.map(|(_, end)| end)
+    }
+
+    /// The length in bytes of the whole resource, or `None` if the server
+    /// does not know it (`*`).
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// Whether this is the `bytes */total` form, which is sent with a `416
+    /// Range Not Satisfiable` response to say how large the resource is.
+    pub fn is_unsatisfiable(&self) -> bool {
+        self.range.is_none()
+    }
+
+    /// Whether the range ends at the last byte of the resource, so that there
+    /// is nothing more to request. This is `false` if the total is unknown.
+    pub fn is_complete(&self) -> bool {
+        matches!((self.range, self.total), (Some((_, end)), Some(total)) if end + 1 == total)
+    }
+}
+
+/// Parse a byte position, which must be nothing but digits.
+fn parse_position(value: &str) -> Option<u64> {
+    match value.bytes().all(|byte| byte.is_ascii_digit()) {
+        true => value.parse().ok(),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContentRange;
+
+    #[test]
+    fn test_parse_complete() {
+        let range = ContentRange::parse("bytes 200-1000/67589").unwrap();
+
+        assert_eq!(range.start(), Some(200));
+        assert_eq!(range.end(), Some(1000));
+        assert_eq!(range.total(), Some(67589));
+        assert!(!range.is_unsatisfiable());
+        assert!(!range.is_complete());
+
+        assert!(ContentRange::parse("bytes 67000-67588/67589")
+            .unwrap()
+            .is_complete());
+    }
+
+    #[test]
+    fn test_parse_unknown_total() {
+        let range = ContentRange::parse("bytes 0-499/*").unwrap();
+
+        assert_eq!(range.start(), Some(0));
+        assert_eq!(range.end(), Some(499));
+        assert_eq!(range.total(), None);
+        assert!(!range.is_complete());
+    }
+
+    #[test]
+    fn test_parse_unsatisfiable() {
+        let range = ContentRange::parse("bytes */1234").unwrap();
+
+        assert!(range.is_unsatisfiable());
+        assert_eq!(range.start(), None);
+        assert_eq!(range.end(), None);
+        assert_eq!(range.total(), Some(1234));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for value in [
+            "items 0-24/100",
+            "bytes */*",
+            "bytes 500-200/1000",
+            "bytes 0-1000/1000",
+            "bytes -5-10/100",
+            "bytes 0-10",
+        ] {
+            assert_eq!(ContentRange::parse(value), None, "{}", value);
+        }
+    }
+}
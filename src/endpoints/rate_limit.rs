@@ -0,0 +1,128 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The rate limit that a server reported in the headers of a response, for
+/// throttling requests before the server starts to reject them.
+///
+/// Two spellings of the headers are recognized. The first is
+/// `X-RateLimit-Limit`, `X-RateLimit-Remaining`, and `X-RateLimit-Reset`, as
+/// sent by GitHub, and many others that followed it, where the reset is a
+/// Unix timestamp in seconds. The second is `RateLimit-Limit`,
+/// `RateLimit-Remaining`, and `RateLimit-Reset`, from the IETF draft, where
+/// the reset is a number of seconds from when the response was received.
+///
+/// Get this from a response with [`ApiResponse::rate_limit`].
+///
+/// [`ApiResponse::rate_limit`]: crate::endpoints::ApiResponse::rate_limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The maximum number of requests that may be made in the window.
+    pub limit: u64,
+    /// The number of requests that may still be made in the window.
+    pub remaining: u64,
+    /// The time when the window resets, and `remaining` is back to `limit`.
+    pub reset: SystemTime,
+}
+
+impl RateLimit {
+    /// Parse the rate limit from `headers`, counting a relative reset from
+    /// `now`. `None` if any of the three headers is missing, or not a number,
+    /// or if the reset is too far in the future to be represented.
+    pub(crate) fn from_headers(headers: &http::HeaderMap, now: SystemTime) -> Option<Self> {
+        let header =
+            |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.trim().parse().ok() };
+
+        if let (Some(limit), Some(remaining), Some(reset)) = (
+            header("X-RateLimit-Limit"),
+            header("X-RateLimit-Remaining"),
+            header("X-RateLimit-Reset")
+                .and_then(|reset| UNIX_EPOCH.checked_add(Duration::from_secs(reset))),
+        ) {
+            return Some(Self {
+                limit,
+                remaining,
+                reset,
+            });
+        }
+
+        Some(Self {
+            limit: header("RateLimit-Limit")?,
+            remaining: header("RateLimit-Remaining")?,
+            reset: now.checked_add(Duration::from_secs(header("RateLimit-Reset")?))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::RateLimit;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> http::HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| {
+                (
+                    http::HeaderName::from_static(name),
+                    http::HeaderValue::from_static(value),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        // GitHub's spelling, with the reset as a timestamp.
+        let github = headers(&[
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "4987"),
+            ("x-ratelimit-reset", "1700003600"),
+        ]);
+        assert_eq!(
+            RateLimit::from_headers(&github, now),
+            Some(RateLimit {
+                limit: 5000,
+                remaining: 4987,
+                reset: UNIX_EPOCH + Duration::from_secs(1_700_003_600),
+            })
+        );
+
+        // The IETF draft's spelling, with the reset as seconds from now.
+        let draft = headers(&[
+            ("ratelimit-limit", "100"),
+            ("ratelimit-remaining", "0"),
+            ("ratelimit-reset", "30"),
+        ]);
+        assert_eq!(
+            RateLimit::from_headers(&draft, now),
+            Some(RateLimit {
+                limit: 100,
+                remaining: 0,
+                reset: now + Duration::from_secs(30),
+            })
+        );
+
+        // Every field is required.
+        let partial = headers(&[
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "4987"),
+        ]);
+        assert_eq!(RateLimit::from_headers(&partial, now), None);
+
+        // A reset that overflows the time is as good as missing.
+        let overflow = headers(&[
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "18446744073709551615"),
+        ]);
+        assert_eq!(RateLimit::from_headers(&overflow, now), None);
+        let overflow = headers(&[
+            ("ratelimit-limit", "100"),
+            ("ratelimit-remaining", "0"),
+            ("ratelimit-reset", "18446744073709551615"),
+        ]);
+        assert_eq!(RateLimit::from_headers(&overflow, now), None);
+    }
+}
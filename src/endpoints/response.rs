@@ -1,4 +1,18 @@
+use std::io;
 use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_lite::io::AsyncRead;
+
+use serde::de::DeserializeOwned;
+
+use crate::endpoints::errors::DeserializeError;
+use crate::endpoints::format::{Json, ResponseFormat};
+use crate::endpoints::range::ContentRange;
+use crate::endpoints::rate_limit::RateLimit;
+#[cfg(feature = "timings")]
+use crate::endpoints::timings::Timings;
 
 /// Result of a successful API request returned from and endpoint function.
 ///
@@ -9,17 +23,147 @@ use std::ops::{Deref, DerefMut};
 ///
 /// It implements [`Deref`] and [`DerefMut`] to provide easy access to the inner
 /// deserialized value of type `T`. It also contains the original body bytes of
-/// the response.
+/// the response, and its status and headers.
+///
+/// With the `timings` feature, responses from the [`endpoint!`] macro also
+/// carry the [`Timings`] of the request that they were received from.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ApiResponse<T> {
     bytes: Vec<u8>,
     value: T,
+    status: http::StatusCode,
+    headers: http::HeaderMap,
+    #[cfg(feature = "timings")]
+    timings: Option<Timings>,
+}
+
+/// Measures the time from sending a request to having read its response, for
+/// [`ApiResponse::__with_parts`]. This does nothing without the `timings`
+/// feature, so that the [`endpoint!`] macro can use it unconditionally.
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+#[doc(hidden)]
+pub struct __Stopwatch {
+    #[cfg(feature = "timings")]
+    started: std::time::Instant,
+}
+
+impl __Stopwatch {
+    #[doc(hidden)]
+    pub fn __start() -> Self {
+        Self {
+            #[cfg(feature = "timings")]
+            started: std::time::Instant::now(),
+        }
+    }
 }
 
 impl<T> ApiResponse<T> {
     #[doc(hidden)]
     pub fn __new(bytes: Vec<u8>, value: T) -> Self {
-        Self { bytes, value }
+        Self {
+            bytes,
+            value,
+            status: http::StatusCode::OK,
+            headers: http::HeaderMap::new(),
+            #[cfg(feature = "timings")]
+            timings: None,
+        }
+    }
+
+    /// Attach the status and headers from the `parts` of the response that the
+    /// body was read from. With the `timings` feature, this also attaches the
+    /// [`Timings`] that the backend inserted into the extensions, if any, with
+    /// the total measured by `stopwatch`.
+    #[doc(hidden)]
+    #[allow(unused_variables)]
+    pub fn __with_parts(mut self, parts: http::response::Parts, stopwatch: __Stopwatch) -> Self {
+        #[cfg(feature = "timings")]
+        {
+            let mut timings = parts
+                .extensions
+                .get::<Timings>()
+                .copied()
+                .unwrap_or_default();
+            timings
+                .total
+                .get_or_insert_with(|| stopwatch.started.elapsed());
+            self.timings = Some(timings);
+        }
+
+        self.status = parts.status;
+        self.headers = parts.headers;
+        self
+    }
+
+    /// Get the status of the response. This is always `200 OK` for a response
+    /// that was not received by the [`endpoint!`] macro, such as one made with
+    /// [`Self::from_bytes`].
+    ///
+    /// [`endpoint!`]: crate::endpoints::endpoint
+    pub fn status(&self) -> http::StatusCode {
+        self.status
+    }
+
+    /// Get an immutable borrow to the headers of the response, such as the
+    /// `ETag`, or the remaining rate limit. This is empty for a response that
+    /// was not received by the [`endpoint!`] macro, such as one made with
+    /// [`Self::from_bytes`].
+    ///
+    /// [`endpoint!`]: crate::endpoints::endpoint
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+
+    /// Parse the total number of items in a paginated collection from the
+    /// headers of the response, for APIs that report it there rather than in
+    /// the body. This is the value of the `X-Total-Count` header if there is
+    /// one, or otherwise the part after the `/` of a `Content-Range` header,
+    /// such as `items 0-24/100` (as sent by PostgREST). `None` if neither is
+    /// present, or the total is unknown (`*`) or not a number.
+    ///
+    /// This is intended to be returned by [`PaginationDelegate::total_items`],
+    /// after being stored by the delegate when it receives each page.
+    ///
+    /// [`PaginationDelegate::total_items`]: crate::paginator::PaginationDelegate::total_items
+    pub fn total_count(&self) -> Option<usize> {
+        let header = |name| self.headers.get(name)?.to_str().ok();
+
+        match header("X-Total-Count") {
+            Some(total) => total.trim().parse().ok(),
+            None => header("Content-Range")?
+                .rsplit_once('/')?
+                .1
+                .trim()
+                .parse()
+                .ok(),
+        }
+    }
+
+    /// Parse the `Content-Range` header of the response, which a server sends
+    /// with `206 Partial Content` when only part of a resource was requested
+    /// with a `Range` header. `None` if there is no such header, or it is not
+    /// a valid range of bytes.
+    pub fn content_range(&self) -> Option<ContentRange> {
+        content_range(&self.headers)
+    }
+
+    /// Parse the rate limit that the server reported in the headers of the
+    /// response. See [`RateLimit`] for the headers that are recognized. `None`
+    /// if the limit, the remaining number of requests, or the reset is
+    /// missing. A reset that is relative is counted from when this is called.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        RateLimit::from_headers(&self.headers, std::time::SystemTime::now())
+    }
+
+    /// Get the [`Timings`] of the request that this response was received
+    /// from. This is `None` if the response was not received by the
+    /// [`endpoint!`] macro, such as one made with [`Self::from_bytes`].
+    ///
+    /// [`endpoint!`]: crate::endpoints::endpoint
+    #[cfg(feature = "timings")]
+    pub fn timings(&self) -> Option<&Timings> {
+        self.timings.as_ref()
     }
 
     /// Get an immutable borrow to the response's body bytes.
@@ -46,6 +190,99 @@ impl<T> ApiResponse<T> {
     pub fn into_bytes_value(self) -> (Vec<u8>, T) {
         (self.bytes, self.value)
     }
+
+    /// Transform the deserialized value with `f`, such as to unwrap it from an
+    /// envelope that the API puts around every response. The body bytes, the
+    /// status, and the headers are carried over to the new response as they
+    /// are.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ApiResponse<U> {
+        ApiResponse {
+            bytes: self.bytes,
+            value: f(self.value),
+            status: self.status,
+            headers: self.headers,
+            #[cfg(feature = "timings")]
+            timings: self.timings,
+        }
+    }
+
+    /// Same as [`Self::map`], but with a transformation that may fail, in
+    /// which case the error is returned and the response is discarded.
+    pub fn try_map<U, E>(self, f: impl FnOnce(T) -> Result<U, E>) -> Result<ApiResponse<U>, E> {
+        Ok(ApiResponse {
+            bytes: self.bytes,
+            value: f(self.value)?,
+            status: self.status,
+            headers: self.headers,
+            #[cfg(feature = "timings")]
+            timings: self.timings,
+        })
+    }
+}
+
+impl<T> ApiResponse<T>
+where
+    T: DeserializeOwned,
+{
+    /// Deserialize the body bytes of a response as JSON, and wrap both the
+    /// bytes and the value together. This is the same logic that the
+    /// [`endpoint!`] macro uses after receiving a successful response, exposed
+    /// so that responses can be reconstructed from bytes stored elsewhere, such
+    /// as a cache or a test fixture.
+    ///
+    /// An empty body, such as that of a `204 No Content` response, is
+    /// deserialized as if it were `null`. This makes it possible to use `()`
+    /// or an [`Option`] as `T`, for endpoints that may not return a body.
+    ///
+    /// The `uri` is not read from or requested; it is only stored in the
+    /// [`DeserializeError`] if the bytes fail to deserialize into `T`, because
+    /// every error of a request carries the URI that it was made to, and the
+    /// [`endpoint!`] macro reports failures with it. Bytes that did not come
+    /// from a request can be converted with `TryFrom<&[u8]>` instead, which
+    /// uses [`BYTES_URI`] as a placeholder.
+    ///
+    /// [`endpoint!`]: crate::endpoints::endpoint
+    #[allow(clippy::result_large_err)]
+    pub fn from_bytes(uri: &url::Url, bytes: Vec<u8>) -> Result<Self, DeserializeError> {
+        Self::from_bytes_as::<Json>(uri, bytes)
+    }
+
+    /// Same as [`Self::from_bytes`], but deserializing the bytes with the
+    /// [`ResponseFormat`] `F`, rather than as JSON.
+    #[allow(clippy::result_large_err)]
+    pub fn from_bytes_as<F>(
+        uri: &url::Url,
+        bytes: Vec<u8>,
+    ) -> Result<Self, DeserializeError<F::Error>>
+    where
+        F: ResponseFormat,
+    {
+        match F::deserialize(&bytes) {
+            Ok(value) => Ok(Self::__new(bytes, value)),
+            Err(error) => Err(DeserializeError::__new(uri.clone(), bytes, error)),
+        }
+    }
+}
+
+/// The URI in the [`DeserializeError`] of an [`ApiResponse`] that was
+/// converted from bytes with `TryFrom<&[u8]>`, which has no request to report
+/// the URI of.
+pub const BYTES_URI: &str = "about:blank";
+
+impl<T> TryFrom<&[u8]> for ApiResponse<T>
+where
+    T: DeserializeOwned,
+{
+    type Error = DeserializeError;
+
+    /// Same as [`ApiResponse::from_bytes`], with [`BYTES_URI`] as the URI.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        // Use of unwrap:
+        // The placeholder is a constant that is known to be a valid URI.
+        let uri = url::Url::parse(BYTES_URI).unwrap();
+
+        Self::from_bytes(&uri, bytes.to_vec())
+    }
 }
 
 impl<T> Deref for ApiResponse<T> {
@@ -61,3 +298,289 @@ impl<T> DerefMut for ApiResponse<T> {
         &mut self.value
     }
 }
+
+/// Result of a successful API request made by the [`endpoint!`] macro with
+/// `stream_body: true`, where the body of the response has not been read.
+///
+/// This is for large responses, such as downloads, that should be piped
+/// somewhere else rather than buffered in memory and deserialized. The status
+/// and headers have already been received, and the body is read from this
+/// with [`AsyncRead`], at whatever pace the caller chooses. `B` is the
+/// [`Backend::Body`] of the client that the request was sent with.
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+/// [`Backend::Body`]: crate::endpoints::Backend::Body
+#[derive(Debug)]
+pub struct StreamingApiResponse<B> {
+    body: B,
+    status: http::StatusCode,
+    headers: http::HeaderMap,
+}
+
+impl<B> StreamingApiResponse<B> {
+    #[doc(hidden)]
+    pub fn __new(parts: http::response::Parts, body: B) -> Self {
+        Self {
+            body,
+            status: parts.status,
+            headers: parts.headers,
+        }
+    }
+
+    /// Get the status of the response.
+    pub fn status(&self) -> http::StatusCode {
+        self.status
+    }
+
+    /// Get an immutable borrow to the headers of the response, such as the
+    /// `Content-Length` or `Content-Type` of the body.
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+
+    /// Parse the `Content-Range` header of the response. This is the same as
+    /// [`ApiResponse::content_range`], and is most useful for downloads that
+    /// are resumed, or split into several requests.
+    pub fn content_range(&self) -> Option<ContentRange> {
+        content_range(&self.headers)
+    }
+
+    /// Get a mutable borrow to the reader of the body. This is the same as
+    /// reading from the response itself.
+    pub fn body_mut(&mut self) -> &mut B {
+        &mut self.body
+    }
+
+    /// Take out the reader of the body, discarding the status and headers.
+    pub fn into_body(self) -> B {
+        self.body
+    }
+}
+
+fn content_range(headers: &http::HeaderMap) -> Option<ContentRange> {
+    ContentRange::parse(headers.get(http::header::CONTENT_RANGE)?.to_str().ok()?)
+}
+
+impl<B> AsyncRead for StreamingApiResponse<B>
+where
+    B: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().body).poll_read(ctx, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::{__Stopwatch, ApiResponse, BYTES_URI};
+
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    struct Project {
+        slug: String,
+        downloads: u64,
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let uri = url::Url::parse("https://example.com/project/awaur").unwrap();
+        let bytes = br#"{"slug":"awaur","downloads":1024}"#.to_vec();
+        let response = ApiResponse::<Project>::from_bytes(&uri, bytes.clone()).unwrap();
+
+        assert_eq!(response.bytes(), bytes.as_slice());
+        assert_eq!(response.slug, "awaur");
+        assert_eq!(
+            response.into_value(),
+            Project {
+                slug: "awaur".to_owned(),
+                downloads: 1024
+            }
+        );
+    }
+
+    #[test]
+    fn test_try_from_bytes() {
+        let bytes = br#"{"slug":"awaur","downloads":1024}"#.as_slice();
+        let response = ApiResponse::<Project>::try_from(bytes).unwrap();
+
+        assert_eq!(response.bytes(), bytes);
+        assert_eq!(response.downloads, 1024);
+
+        let bytes = br#"{"slug":"awaur"}"#.as_slice();
+        let error = ApiResponse::<Project>::try_from(bytes).unwrap_err();
+
+        assert_eq!(error.uri().as_str(), BYTES_URI);
+        assert_eq!(error.bytes(), bytes);
+    }
+
+    #[test]
+    fn test_map() {
+        let uri = url::Url::parse("https://example.com/project/awaur").unwrap();
+        let bytes = br#"{"slug":"awaur","downloads":1024}"#.to_vec();
+        let response = ApiResponse::<Project>::from_bytes(&uri, bytes.clone()).unwrap();
+
+        let mapped = response.clone().map(|project| project.downloads);
+        assert_eq!(mapped.bytes(), bytes.as_slice());
+        assert_eq!(mapped.into_value(), 1024);
+
+        let mapped = response
+            .clone()
+            .try_map(|project| u8::try_from(project.downloads / 8));
+        assert_eq!(mapped.unwrap().into_bytes_value(), (bytes, 128));
+        assert!(response
+            .try_map(|project| u8::try_from(project.downloads))
+            .is_err());
+    }
+
+    #[test]
+    fn test_total_count() {
+        let uri = url::Url::parse("https://example.com/projects").unwrap();
+        let response = |name: &'static str, value: &'static str| {
+            let mut parts = http::Response::new(()).into_parts().0;
+            parts
+                .headers
+                .insert(name, http::HeaderValue::from_static(value));
+            ApiResponse::<()>::from_bytes(&uri, Vec::new())
+                .unwrap()
+                .__with_parts(parts, __Stopwatch::__start())
+        };
+
+        assert_eq!(response("X-Total-Count", "42").total_count(), Some(42));
+        assert_eq!(
+            response("Content-Range", "items 0-24/100").total_count(),
+            Some(100)
+        );
+        assert_eq!(
+            response("Content-Range", "items 0-24/*").total_count(),
+            None
+        );
+        assert_eq!(response("ETag", "\"abc\"").total_count(), None);
+    }
+
+    #[test]
+    fn test_content_range() {
+        let uri = url::Url::parse("https://example.com/archive.zip").unwrap();
+        let mut parts = http::Response::new(()).into_parts().0;
+        parts.headers.insert(
+            "Content-Range",
+            http::HeaderValue::from_static("bytes 200-1000/67589"),
+        );
+        let response = ApiResponse::<()>::from_bytes(&uri, Vec::new())
+            .unwrap()
+            .__with_parts(parts, __Stopwatch::__start());
+
+        let range = response.content_range().unwrap();
+        assert_eq!(
+            (range.start(), range.end(), range.total()),
+            (Some(200), Some(1000), Some(67589))
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_error() {
+        let uri = url::Url::parse("https://example.com/project/awaur").unwrap();
+        let bytes = br#"{"slug":"awaur","downloads":"many"}"#.to_vec();
+        let error = ApiResponse::<Project>::from_bytes(&uri, bytes.clone()).unwrap_err();
+
+        assert_eq!(error.uri(), &uri);
+        assert_eq!(error.bytes(), bytes.as_slice());
+        assert_eq!(error.path().to_string(), "downloads");
+    }
+
+    /// Paginating with the total from [`ApiResponse::total_count`].
+    #[cfg(all(feature = "paginator", feature = "testing"))]
+    mod paginate {
+        use async_trait::async_trait;
+
+        use crate::endpoints::{endpoint, ApiResponse, RequestRecorder, TestError};
+        use crate::paginator::{PaginatedStream, PaginationDelegate};
+
+        /// Pages over a collection that only reports its total in a header.
+        struct HeaderTotalDelegate {
+            client: RequestRecorder,
+            base: url::Url,
+            offset: usize,
+            total: Option<usize>,
+        }
+
+        #[async_trait]
+        impl PaginationDelegate for HeaderTotalDelegate {
+            type Item = u32;
+            type Error = TestError;
+
+            async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+                let response = get_items(&self.client, &self.base, self.offset).await?;
+
+                self.total = response.total_count();
+                Ok(response.into_value())
+            }
+
+            fn offset(&self) -> usize {
+                self.offset
+            }
+
+            fn set_offset(&mut self, value: usize) {
+                self.offset = value;
+            }
+
+            fn total_items(&self) -> Option<usize> {
+                self.total
+            }
+        }
+
+        async fn get_items(
+            client: &RequestRecorder,
+            base: &url::Url,
+            offset: usize,
+        ) -> Result<ApiResponse<Vec<u32>>, TestError> {
+            #[derive(serde::Serialize)]
+            struct Params {
+                offset: usize,
+            }
+
+            endpoint! {
+                client GET,
+                uri: base / "items",
+                params: &Params { offset },
+            }
+        }
+
+        #[test]
+        fn test_paginate_header_total() {
+            let client = RequestRecorder::new();
+            let page = |body: &[u8]| {
+                http::Response::builder()
+                    .header("X-Total-Count", "5")
+                    .body(body.to_vec())
+                    .unwrap()
+            };
+
+            client.push_response(page(b"[0,1,2]"));
+            client.push_response(page(b"[3,4]"));
+            // This would only be requested if the total were ignored.
+            client.push_response(page(b"[5,6]"));
+
+            let delegate = HeaderTotalDelegate {
+                client: client.clone(),
+                base: url::Url::parse("https://example.com/").unwrap(),
+                offset: 0,
+                total: None,
+            };
+            let items =
+                futures_lite::future::block_on(PaginatedStream::from(delegate).try_collect_all())
+                    .unwrap();
+
+            assert_eq!(items, [0, 1, 2, 3, 4]);
+            assert_eq!(client.requests().len(), 2);
+            assert_eq!(
+                client.requests()[1].uri,
+                "https://example.com/items?offset=3"
+            );
+        }
+    }
+}
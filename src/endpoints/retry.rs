@@ -0,0 +1,247 @@
+use std::future::Future;
+
+use futures_timer::Delay;
+
+use crate::endpoints::{respect_retry_after, BoxApiError, DeserializeError, ResponseError};
+use crate::retry::RetryPolicy;
+
+/// Determines which failed requests are sent again by [`retry_request`]. How
+/// many times, and how long to wait before each, is up to the [`RetryPolicy`]
+/// that this wraps, except that for a `429 Too Many Requests` or a
+/// `503 Service Unavailable`, the `Retry-After` header is respected instead of
+/// the delay of the policy, if the server sent one.
+///
+/// By default, only requests that were answered with a status that suggests a
+/// transient failure are retried, which are `408 Request Timeout`,
+/// `429 Too Many Requests`, `500 Internal Server Error`, `502 Bad Gateway`,
+/// `503 Service Unavailable`, and `504 Gateway Timeout`. Use
+/// [`Self::retry_deserialize`] to also retry when the body of a successful
+/// response could not be deserialized, such as when it was truncated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestRetryPolicy {
+    policy: RetryPolicy,
+    deserialize: bool,
+}
+
+impl RequestRetryPolicy {
+    /// Retry requests that failed with a transient status, as many times and
+    /// as often as `policy` allows.
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            deserialize: false,
+        }
+    }
+
+    /// Also retry when the body of a response failed to deserialize.
+    ///
+    /// Only use this for idempotent requests, such as `GET`. The server has
+    /// already handled a request that failed this way, so sending a `POST`
+    /// again may, for example, create the same resource twice.
+    pub fn retry_deserialize(mut self) -> Self {
+        self.deserialize = true;
+        self
+    }
+
+    /// Reference to the policy that determines how many times, and how often,
+    /// requests are retried.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Whether a request that failed with `error` should be sent again,
+    /// disregarding the number of attempts.
+    pub fn is_retryable<E>(&self, error: &E) -> bool
+    where
+        E: RequestFailure,
+    {
+        use http::StatusCode;
+
+        match error.response_error().map(ResponseError::status_code) {
+            Some(
+                StatusCode::REQUEST_TIMEOUT
+                | StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::INTERNAL_SERVER_ERROR
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT,
+            ) => true,
+            Some(_) => false,
+            None => self.deserialize && error.is_deserialize_error(),
+        }
+    }
+}
+
+impl From<RetryPolicy> for RequestRetryPolicy {
+    fn from(policy: RetryPolicy) -> Self {
+        Self::new(policy)
+    }
+}
+
+/// The failure of a request, as far as a [`RequestRetryPolicy`] is concerned.
+/// This is implemented for the error types of this module, and should be
+/// implemented for the error type of the endpoints to retry, by forwarding to
+/// whichever of its variants contains one of them.
+pub trait RequestFailure {
+    /// The error for a response with an unsuccessful status, if this is one.
+    fn response_error(&self) -> Option<&ResponseError> {
+        None
+    }
+
+    /// Whether this is an error for a response that was received successfully,
+    /// but could not be deserialized.
+    fn is_deserialize_error(&self) -> bool {
+        false
+    }
+}
+
+impl RequestFailure for ResponseError {
+    fn response_error(&self) -> Option<&ResponseError> {
+        Some(self)
+    }
+}
+
+impl<E> RequestFailure for DeserializeError<E> {
+    fn is_deserialize_error(&self) -> bool {
+        true
+    }
+}
+
+impl RequestFailure for BoxApiError {
+    fn response_error(&self) -> Option<&ResponseError> {
+        self.downcast_ref()
+    }
+
+    fn is_deserialize_error(&self) -> bool {
+        self.is::<DeserializeError>()
+    }
+}
+
+/// Call `send` to make a request, and call it again for as long as it fails
+/// with an error that the `policy` considers retryable, up to the number of
+/// attempts that it allows. The result of the last attempt is returned.
+///
+/// The function `send` is intended to call an endpoint defined with
+/// [`endpoint!`], so that every attempt makes an identical request.
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+pub async fn retry_request<T, E, F, Fut>(policy: RequestRetryPolicy, mut send: F) -> Result<T, E>
+where
+    E: RequestFailure,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match send().await {
+            Err(error) if attempt < policy.policy.attempts() && policy.is_retryable(&error) => {
+                let delay = policy.policy.delay_for(attempt);
+
+                // The server may have asked for a particular delay, which
+                // takes the place of the one from the policy.
+                match error.response_error() {
+                    Some(error)
+                        if matches!(
+                            error.status_code(),
+                            http::StatusCode::TOO_MANY_REQUESTS
+                                | http::StatusCode::SERVICE_UNAVAILABLE
+                        ) =>
+                    {
+                        respect_retry_after(error.status_code(), error.retry_after(), delay).await
+                    }
+                    _ if !delay.is_zero() => Delay::new(delay).await,
+                    _ => (),
+                }
+
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::{retry_request, RequestFailure, RequestRetryPolicy};
+    use crate::endpoints::{endpoint, ApiResponse, RequestRecorder, ResponseError, TestError};
+    use crate::retry::RetryPolicy;
+
+    impl RequestFailure for TestError {
+        fn response_error(&self) -> Option<&ResponseError> {
+            match self {
+                Self::Response(error) => Some(error),
+                _ => None,
+            }
+        }
+
+        fn is_deserialize_error(&self) -> bool {
+            matches!(self, Self::Deserialize(_))
+        }
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct User {
+        id: u32,
+    }
+
+    async fn get_user(client: &RequestRecorder) -> Result<ApiResponse<User>, TestError> {
+        let base = url::Url::parse("https://example.com/").unwrap();
+
+        endpoint! {
+            client GET,
+            uri: base / "user",
+        }
+    }
+
+    fn response(status: u16, body: &[u8]) -> http::Response<Vec<u8>> {
+        http::Response::builder()
+            .status(status)
+            .body(body.to_vec())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_retry_deserialize() {
+        let send = |policy| {
+            let client = RequestRecorder::new();
+            client.push_response(response(200, b"{\"id\":"));
+            client.push_response(response(200, b"{\"id\":1}"));
+
+            let result =
+                futures_lite::future::block_on(retry_request(policy, || get_user(&client)));
+            (result, client.requests().len())
+        };
+
+        // Without opting in, a truncated body is returned as the error it is.
+        let (result, requests) = send(RequestRetryPolicy::new(RetryPolicy::new(2)));
+        assert!(matches!(result, Err(TestError::Deserialize(_))));
+        assert_eq!(requests, 1);
+
+        let (result, requests) =
+            send(RequestRetryPolicy::new(RetryPolicy::new(2)).retry_deserialize());
+        assert_eq!(result.unwrap().into_value(), User { id: 1 });
+        assert_eq!(requests, 2);
+    }
+
+    #[test]
+    fn test_retry_status() {
+        let client = RequestRecorder::new();
+        client.push_response(response(503, b""));
+        client.push_response(response(502, b""));
+        client.push_response(response(404, b""));
+
+        let result =
+            futures_lite::future::block_on(retry_request(RetryPolicy::new(5).into(), || {
+                get_user(&client)
+            }));
+
+        // The `404 Not Found` is not transient, so it is not retried, even
+        // though there are attempts left.
+        match result {
+            Err(TestError::Response(error)) => assert_eq!(error.status_code(), 404),
+            _ => panic!("expected the request to fail with 404"),
+        }
+        assert_eq!(client.requests().len(), 3);
+    }
+}
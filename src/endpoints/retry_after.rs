@@ -0,0 +1,209 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_timer::Delay;
+
+/// Wait before retrying a request that was answered with `status`.
+///
+/// If the status is `429 Too Many Requests` or `503 Service Unavailable`, this
+/// sleeps for `retry_after` (the value of the `Retry-After` header, if the
+/// server sent one), or for `default` otherwise. For any other status, the
+/// server has not asked for the request to be retried later, so this resolves
+/// immediately. This is intended to be awaited at the top of a retry loop,
+/// with the status of a [`ResponseError`], and the duration from
+/// [`ResponseError::retry_after`].
+///
+/// [`ResponseError`]: crate::endpoints::ResponseError
+/// [`ResponseError::retry_after`]: crate::endpoints::ResponseError::retry_after
+pub async fn respect_retry_after(
+    status: http::StatusCode,
+    retry_after: Option<Duration>,
+    default: Duration,
+) {
+    match status {
+        http::StatusCode::TOO_MANY_REQUESTS | http::StatusCode::SERVICE_UNAVAILABLE => {
+            Delay::new(retry_after.unwrap_or(default)).await
+        }
+        _ => {}
+    }
+}
+
+/// Parse the value of a `Retry-After` header, which is either a number of
+/// seconds, or an HTTP-date, which is converted to the time remaining from
+/// `now`. A date that has already passed is zero. All three date formats that
+/// [RFC 9110] requires recipients to accept are parsed, and the day of the
+/// week is ignored.
+///
+/// [RFC 9110]: https://www.rfc-editor.org/rfc/rfc9110#section-5.6.7
+pub(crate) fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+
+    if value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return value.parse().ok().map(Duration::from_secs);
+    }
+
+    let date = UNIX_EPOCH.checked_add(Duration::from_secs(parse_http_date(value)?))?;
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+/// Parse an HTTP-date into the number of seconds since the Unix epoch. Years
+/// after 9999 are rejected, like any other date that is out of range.
+fn parse_http_date(value: &str) -> Option<u64> {
+    let fields = value.split_whitespace().collect::<Vec<_>>();
+
+    let (day, month, year, time) = match fields.as_slice() {
+        // IMF-fixdate, such as `Sun, 06 Nov 1994 08:49:37 GMT`.
+        [_, day, month, year, time, "GMT"] => (*day, *month, year.parse().ok()?, *time),
+        // RFC 850, such as `Sunday, 06-Nov-94 08:49:37 GMT`.
+        [_, date, time, "GMT"] => {
+            let mut parts = date.split('-');
+            let (day, month, year) = (parts.next()?, parts.next()?, parts.next()?);
+            let year = match year.parse::<u64>().ok()? {
+                year if year < 70 => 2000 + year,
+                year if year < 100 => 1900 + year,
+                year => year,
+            };
+            (day, month, year, *time)
+        }
+        // ANSI C's `asctime`, such as `Sun Nov  6 08:49:37 1994`.
+        [_, month, day, time, year] => (*day, *month, year.parse().ok()?, *time),
+        _ => return None,
+    };
+
+    let month = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ]
+    .iter()
+    .position(|name| *name == month)? as u64
+        + 1;
+    let day = day
+        .parse::<u64>()
+        .ok()
+        .filter(|day| (1..=31).contains(day))?;
+
+    let mut clock = time.split(':').map(|part| part.parse::<u64>().ok());
+    let (hours, minutes, seconds) = (clock.next()??, clock.next()??, clock.next()??);
+    if clock.next().is_some() || hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+    if !(1970..=9999).contains(&year) {
+        return None;
+    }
+
+    days_since_epoch(year, month, day)?
+        .checked_mul(86400)?
+        .checked_add(hours * 3600 + minutes * 60 + seconds)
+}
+
+/// The number of days between the Unix epoch and a date in the proleptic
+/// Gregorian calendar, which must not be before it. `None` if the number of
+/// days does not fit in a `u64`.
+fn days_since_epoch(year: u64, month: u64, day: u64) -> Option<u64> {
+    // Count from March, so that the leap day is the last day of the year.
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    // The number of days from 0000-03-01 to 1970-01-01.
+    era.checked_mul(146097)?
+        .checked_add(day_of_era)?
+        .checked_sub(719468)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant, UNIX_EPOCH};
+
+    use super::{parse_retry_after, respect_retry_after};
+
+    #[test]
+    fn test_parse_retry_after() {
+        // Sun, 06 Nov 1994 08:49:00 GMT
+        let now = UNIX_EPOCH + Duration::from_secs(784111740);
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", now),
+            Some(Duration::from_secs(37))
+        );
+        assert_eq!(
+            parse_retry_after("Sunday, 06-Nov-94 08:50:00 GMT", now),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            parse_retry_after("Sun Nov  6 09:49:00 1994", now),
+            Some(Duration::from_secs(3600))
+        );
+        // Leap years, and a date that has already passed.
+        assert_eq!(
+            parse_retry_after("Thu, 29 Feb 2024 00:00:00 GMT", UNIX_EPOCH),
+            Some(Duration::from_secs(1709164800))
+        );
+        assert_eq!(
+            parse_retry_after("Sat, 05 Nov 1994 08:49:37 GMT", now),
+            Some(Duration::ZERO)
+        );
+
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-5", now), None);
+        assert_eq!(
+            parse_retry_after("Sun, 06 Foo 1994 08:49:37 GMT", now),
+            None
+        );
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 25:49:37 GMT", now),
+            None
+        );
+        // Years that are out of range, rather than overflowing.
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 10000 08:49:37 GMT", now),
+            None
+        );
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 300000000000 08:49:37 GMT", now),
+            None
+        );
+        assert_eq!(
+            parse_retry_after("Sun Nov  6 08:49:37 18446744073709551615", now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_respect_retry_after() {
+        let default = Duration::from_secs(10);
+
+        let started = Instant::now();
+        futures_lite::future::block_on(respect_retry_after(
+            http::StatusCode::TOO_MANY_REQUESTS,
+            Some(Duration::from_millis(50)),
+            default,
+        ));
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(50) && elapsed < default);
+
+        let started = Instant::now();
+        futures_lite::future::block_on(respect_retry_after(
+            http::StatusCode::SERVICE_UNAVAILABLE,
+            None,
+            Duration::from_millis(20),
+        ));
+        assert!(started.elapsed() >= Duration::from_millis(20));
+
+        let started = Instant::now();
+        futures_lite::future::block_on(respect_retry_after(
+            http::StatusCode::NOT_FOUND,
+            Some(default),
+            default,
+        ));
+        assert!(started.elapsed() < Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,96 @@
+use jsonschema::JSONSchema;
+use serde_json::Value;
+
+use crate::endpoints::errors::SchemaError;
+
+/// Check that the body `bytes` of a response conform to the `schema`. Bytes
+/// that are not valid JSON are left for deserialization to report, and an
+/// empty body is checked as if it were `null`, the same as
+/// [`ApiResponse::from_bytes`].
+///
+/// [`ApiResponse::from_bytes`]: crate::endpoints::ApiResponse::from_bytes
+#[doc(hidden)]
+#[allow(clippy::result_large_err)]
+pub fn __validate_schema(
+    uri: &url::Url,
+    bytes: &[u8],
+    schema: &JSONSchema,
+) -> Result<(), SchemaError> {
+    let instance = match bytes {
+        [] => Value::Null,
+        bytes => match serde_json::from_slice(bytes) {
+            Ok(instance) => instance,
+            Err(_) => return Ok(()),
+        },
+    };
+
+    let messages = match schema.validate(&instance) {
+        Ok(()) => return Ok(()),
+        Err(errors) => errors
+            .map(|error| format!("{}: {}", error.instance_path, error))
+            .collect(),
+    };
+
+    Err(SchemaError::__new(uri.clone(), bytes.to_vec(), messages))
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use jsonschema::JSONSchema;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use crate::endpoints::{endpoint, ApiResponse, RequestRecorder, TestError};
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Project {
+        slug: String,
+        downloads: i64,
+    }
+
+    async fn get_project(
+        client: &RequestRecorder,
+        base: &url::Url,
+        schema: &JSONSchema,
+    ) -> Result<ApiResponse<Project>, TestError> {
+        endpoint! {
+            client GET,
+            uri: base / "project",
+            schema: schema,
+        }
+    }
+
+    #[test]
+    fn test_schema() {
+        let recorder = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/").unwrap();
+        let schema = JSONSchema::compile(&json!({
+            "type": "object",
+            "properties": {
+                "downloads": { "type": "integer", "minimum": 0 },
+            },
+        }))
+        .unwrap();
+
+        recorder.push_response(http::Response::new(
+            br#"{"slug":"awaur","downloads":1024}"#.to_vec(),
+        ));
+        recorder.push_response(http::Response::new(
+            br#"{"slug":"awaur","downloads":-1}"#.to_vec(),
+        ));
+
+        let result = futures_lite::future::block_on(get_project(&recorder, &base, &schema));
+        assert_eq!(result.unwrap().downloads, 1024);
+
+        // This would deserialize, but a negative count is not allowed.
+        let result = futures_lite::future::block_on(get_project(&recorder, &base, &schema));
+        match result {
+            Err(TestError::Schema(error)) => {
+                assert_eq!(error.messages().len(), 1);
+                assert!(error.messages()[0].starts_with("/downloads: "));
+            }
+            _ => panic!("expected the body to violate the schema"),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures_lite::io::Cursor;
+
+use crate::endpoints::backend::Backend;
+
+/// A stand-in for an HTTP client that never touches the network. Every request
+/// sent with it is captured into a shared buffer, and a canned response is
+/// returned in its place.
+///
+/// This implements [`Backend`], so it can be passed as the `$client` of any
+/// endpoint function that uses the [`endpoint!`] macro. After the endpoint has
+/// been called, the recorded requests can be inspected to assert that the
+/// method, URI, headers, and body are what was expected.
+///
+/// Clones share the same buffers, so a clone can be handed to the code under
+/// test while the original is kept for making assertions.
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+#[derive(Debug, Clone, Default)]
+pub struct RequestRecorder {
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    responses: Arc<Mutex<VecDeque<CannedResponse>>>,
+}
+
+/// The parts of a request that have been captured by a [`RequestRecorder`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    /// The method that the request was made with.
+    pub method: http::Method,
+    /// The full URI, including the query string, that the request was made to.
+    pub uri: http::Uri,
+    /// Every header that was set on the request.
+    pub headers: http::HeaderMap,
+    /// The body bytes of the request, empty if there was no body.
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct CannedResponse {
+    status: http::StatusCode,
+    headers: http::HeaderMap,
+    body: Vec<u8>,
+}
+
+impl Default for CannedResponse {
+    fn default() -> Self {
+        Self {
+            status: http::StatusCode::OK,
+            headers: http::HeaderMap::new(),
+            body: b"null".to_vec(),
+        }
+    }
+}
+
+impl RequestRecorder {
+    /// Create a recorder with no recorded requests. Until a response is
+    /// pushed, every request will be answered with `200 OK` and a body of
+    /// `null`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a canned response. Responses are returned in the order that they
+    /// were pushed, and the last one remaining will be repeated for every
+    /// request after it.
+    pub fn push_response(&self, response: http::Response<Vec<u8>>) {
+        let (parts, body) = response.into_parts();
+
+        self.responses.lock().unwrap().push_back(CannedResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        });
+    }
+
+    /// Copy of every request that has been recorded so far, in the order that
+    /// they were sent.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Take out every request that has been recorded so far, leaving the
+    /// buffer empty.
+    pub fn take_requests(&self) -> Vec<RecordedRequest> {
+        std::mem::take(&mut *self.requests.lock().unwrap())
+    }
+}
+
+#[async_trait]
+impl Backend for RequestRecorder {
+    /// Recording a request never fails, so the error type of any endpoint
+    /// function using this must implement `From<RequestError<Infallible>>`.
+    type Error = Infallible;
+    type Body = Cursor<Vec<u8>>;
+
+    /// Record the request and respond with the next canned response.
+    async fn send_request(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Self::Body>, Self::Error> {
+        let (parts, body) = request.into_parts();
+
+        self.requests.lock().unwrap().push(RecordedRequest {
+            method: parts.method,
+            uri: parts.uri,
+            headers: parts.headers,
+            body,
+        });
+
+        let canned = {
+            let mut responses = self.responses.lock().unwrap();
+
+            if responses.len() > 1 {
+                responses.pop_front().unwrap()
+            } else {
+                responses.front().cloned().unwrap_or_default()
+            }
+        };
+
+        let mut response = http::Response::new(Cursor::new(canned.body));
+        *response.status_mut() = canned.status;
+        *response.headers_mut() = canned.headers;
+
+        Ok(response)
+    }
+}
+
+/// The error type of the endpoint functions in the tests of this crate, which
+/// can be converted from every error that the [`endpoint!`] macro returns. `E`
+/// is the [`Backend::Error`] of the client, and `D` is the error of the
+/// [`ResponseFormat`], if it isn't JSON.
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+/// [`ResponseFormat`]: crate::endpoints::ResponseFormat
+#[cfg(test)]
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum TestError<E = Infallible, D = serde_json::Error> {
+    #[error(transparent)]
+    Request(#[from] crate::endpoints::RequestError<E>),
+    #[error(transparent)]
+    Timeout(#[from] crate::endpoints::RequestTimeoutError),
+    #[error(transparent)]
+    Response(#[from] crate::endpoints::ResponseError),
+    #[error(transparent)]
+    BodyRead(#[from] crate::endpoints::BodyReadError),
+    #[error(transparent)]
+    Deserialize(#[from] crate::endpoints::DeserializeError<D>),
+    #[error(transparent)]
+    JsonPatch(#[from] crate::endpoints::JsonPatchError),
+    #[error(transparent)]
+    Form(#[from] crate::endpoints::FormError),
+    #[cfg(feature = "jsonschema")]
+    #[error(transparent)]
+    Schema(#[from] crate::endpoints::SchemaError),
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::RequestRecorder;
+    use crate::endpoints::{endpoint, ApiResponse, TestError};
+
+    #[derive(Serialize)]
+    struct NoteParams {
+        draft: bool,
+    }
+
+    #[derive(Serialize)]
+    struct NoteBody<'a> {
+        text: &'a str,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Note {
+        id: u32,
+        text: String,
+    }
+
+    async fn create_note(
+        client: &RequestRecorder,
+        base: &url::Url,
+        folder: &str,
+        text: &str,
+    ) -> Result<ApiResponse<Note>, TestError> {
+        endpoint! {
+            client POST,
+            uri: base / "folders/{}/notes",
+            vars: [folder],
+            params: &NoteParams { draft: true },
+            body: &NoteBody { text },
+        }
+    }
+
+    #[test]
+    fn test_recorded_request() {
+        let recorder = RequestRecorder::new();
+        let base = url::Url::parse("https://example.com/api/").unwrap();
+
+        recorder.push_response(
+            http::Response::builder()
+                .status(200)
+                .body(br#"{"id":7,"text":"hello"}"#.to_vec())
+                .unwrap(),
+        );
+
+        let response =
+            futures_lite::future::block_on(create_note(&recorder, &base, "inbox", "hello"))
+                .unwrap();
+
+        // The canned response should have been deserialized like any other.
+        assert_eq!(
+            response.value(),
+            &Note {
+                id: 7,
+                text: "hello".to_owned()
+            }
+        );
+
+        let requests = recorder.take_requests();
+        assert_eq!(requests.len(), 1);
+
+        let request = &requests[0];
+        assert_eq!(request.method, http::Method::POST);
+        assert_eq!(
+            request.uri,
+            "https://example.com/api/folders/inbox/notes?draft=true"
+        );
+        assert_eq!(request.headers.len(), 1);
+        assert_eq!(
+            request.headers[http::header::CONTENT_TYPE],
+            "application/json"
+        );
+        assert_eq!(request.body, br#"{"text":"hello"}"#);
+        assert!(recorder.requests().is_empty());
+    }
+}
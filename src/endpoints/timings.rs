@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+/// How long each phase of a request took, as far as it is known. Every duration
+/// is measured from the moment that the request started, so each one includes
+/// the phases before it.
+///
+/// The [`endpoint!`] macro measures [`Self::total`] itself, and attaches these
+/// to the [`ApiResponse`]. The other phases can only be measured by the HTTP
+/// client, so a [`Backend`] provides them by inserting a `Timings` into the
+/// extensions of the response it returns. Any phase that the backend doesn't
+/// report is `None`.
+///
+/// The `isahc` backend reports every phase, but only if metrics have been
+/// enabled on the client with `HttpClientBuilder::metrics`.
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+/// [`ApiResponse`]: crate::endpoints::ApiResponse
+/// [`Backend`]: crate::endpoints::Backend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Time until the host name was resolved.
+    pub name_lookup: Option<Duration>,
+    /// Time until the connection to the host was established, including any
+    /// TLS handshake.
+    pub connect: Option<Duration>,
+    /// Time until the first byte of the response was received.
+    pub first_byte: Option<Duration>,
+    /// Time until the body of the response was read to the end. Unless the
+    /// backend provides it, this is measured from when the request was handed
+    /// to the backend.
+    pub total: Option<Duration>,
+}
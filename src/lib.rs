@@ -8,7 +8,21 @@ pub mod endpoints;
 pub mod macros;
 #[cfg(feature = "paginator")]
 pub mod paginator;
-#[cfg(any(feature = "serde-with-base62", feature = "serde-with-json-string"))]
+#[cfg(any(feature = "endpoints", feature = "paginator"))]
+pub mod retry;
+#[cfg(any(
+    feature = "serde-with-always-null",
+    feature = "serde-with-base62",
+    feature = "serde-with-base64",
+    feature = "serde-with-delimited",
+    feature = "serde-with-hex",
+    feature = "serde-with-iso8601-duration",
+    feature = "serde-with-json-string",
+    feature = "serde-with-lenient-float",
+    feature = "serde-with-bitflags",
+    feature = "serde-with-map-as-entries",
+    feature = "serde-with-unwrap-single"
+))]
 pub mod serde_with;
 
 #[cfg(test)]
@@ -17,31 +17,207 @@ use macro_pub::macro_pub;
 /// - Because this declares *and* instantiates, the fields follow the same
 ///   syntax as a variable declaration. The types of the fields cannot be
 ///   elided.
+/// - The `= value` of a field can be omitted, in which case it is initialized
+///   with [`Default::default`], so its type must implement [`Default`]. The
+///   structure itself does not need to.
 /// - Field names can be prefixed with a visibility qualifier, same as a
 ///   structure definition.
-/// - If lifetimes are used in field types, they must be included in angle
-///   brackets after the structure name, same as a declaration.
+/// - If lifetimes or type parameters are used in field types, they must be
+///   included in angle brackets after the structure name, with any bounds,
+///   same as a declaration. A `where` clause may follow them. The arguments
+///   are inferred from the values when the structure is instantiated.
+/// - A tuple structure is declared with parentheses instead of braces, and
+///   its fields are only types, optionally followed by `= value`. A `where`
+///   clause goes after the parentheses, same as a declaration.
 #[macro_pub]
 macro_rules! new_struct {
     (
         $(#[$struct_meta:meta])*
-        $struct_name:ident $(<$($struct_life:lifetime),+>)? {
-            $(
-                $(#[$field_meta:meta])*
-                $field_vis:vis $field_name:ident: $field_type:ty = $field_value:expr,
-            )+
-        }
-    ) => {{
-        $(#[$struct_meta])*
-        struct $struct_name $(<$($struct_life),*>)? {
+        $struct_name:ident $($rest:tt)+
+    ) => {
+        $crate::macros::new_struct!(@munch [$(#[$struct_meta])*] $struct_name [] $($rest)+)
+    };
+    // The generics and the `where` clause are copied as they are, so they are
+    // collected one token at a time until all that is left is the body.
+    (@munch [$($struct_meta:tt)*] $struct_name:ident [$($generics:tt)*] {
+        $(
+            $(#[$field_meta:meta])*
+            $field_vis:vis $field_name:ident: $field_type:ty $(= $field_value:expr)?
+        ),+ $(,)?
+    }) => {{
+        $($struct_meta)*
+        struct $struct_name $($generics)* {
             $(
                 $(#[$field_meta])*
                 $field_vis $field_name: $field_type,
-            )*
+            )+
         }
 
         $struct_name {
-            $($field_name: $field_value,)*
+            $($field_name: $crate::macros::new_struct!(@value $($field_value)?),)+
         }
     }};
+    // Parentheses are also the arguments of a bound such as `Fn(u8)`, but
+    // those are never followed by a `where` clause or the end of the input.
+    (@munch [$($struct_meta:tt)*] $struct_name:ident [$($generics:tt)*] (
+        $(
+            $(#[$field_meta:meta])*
+            $field_vis:vis $field_type:ty $(= $field_value:expr)?
+        ),+ $(,)?
+    ) $(where $($where:tt)+)?) => {{
+        $($struct_meta)*
+        struct $struct_name $($generics)* (
+            $(
+                $(#[$field_meta])*
+                $field_vis $field_type,
+            )+
+        ) $(where $($where)+)?;
+
+        $struct_name(
+            $($crate::macros::new_struct!(@value $($field_value)?),)+
+        )
+    }};
+    (@munch [$($struct_meta:tt)*] $struct_name:ident [$($generics:tt)*] $next:tt $($rest:tt)+) => {
+        $crate::macros::new_struct!(@munch [$($struct_meta)*] $struct_name [$($generics)* $next] $($rest)+)
+    };
+    (@value) => {
+        ::std::default::Default::default()
+    };
+    (@value $field_value:expr) => {
+        $field_value
+    };
+}
+
+#[doc(hidden)]
+#[cfg(any(feature = "endpoints", feature = "serde-with-json-string"))]
+pub use serde_json as __serde_json;
+
+/// Same as [`new_struct!`], but also serializes the structure into a
+/// [`serde_json::Value`], and evaluates to a tuple of both. This is intended
+/// for tests that need an ad-hoc request body, where the structure gives a
+/// typed handle to the fields, and the value is what is expected to be sent.
+/// Pass a reference to the structure as the `$body` of the [`endpoint!`]
+/// macro.
+///
+/// The structure must implement [`serde::Serialize`], so usually it should be
+/// preceded by `#[derive(Serialize)]`. Fields without a value are initialized
+/// with [`Default::default`], the same as for [`new_struct!`]. Only available
+/// with a feature that depends on [`serde_json`], such as `endpoints`.
+///
+/// [`endpoint!`]: crate::endpoints::endpoint
+/// [`serde_json::Value`]: https://docs.rs/serde_json/latest/serde_json/enum.Value.html
+/// [`serde::Serialize`]: https://docs.rs/serde/latest/serde/trait.Serialize.html
+/// [`serde_json`]: https://docs.rs/serde_json/latest/serde_json/
+#[cfg(any(feature = "endpoints", feature = "serde-with-json-string"))]
+#[macro_pub]
+macro_rules! json_struct {
+    ($($struct_tokens:tt)+) => {{
+        let value = $crate::macros::new_struct! { $($struct_tokens)+ };
+        // Use of unwrap:
+        // Same as the `$body` of the `endpoint!` macro, the structure is
+        // expected to have a well-defined serialization, which would only fail
+        // for things such as a map with keys that aren't strings.
+        let json = $crate::macros::__serde_json::to_value(&value).unwrap();
+
+        (value, json)
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_new_struct_defaults() {
+        let options = crate::macros::new_struct! {
+            #[derive(Debug)]
+            Options<'a> {
+                name: &'a str = "awaur",
+                retries: u32,
+                tags: Vec<String>,
+                verbose: bool = true,
+            }
+        };
+
+        assert_eq!(options.name, "awaur");
+        assert_eq!(options.retries, 0);
+        assert!(options.tags.is_empty());
+        assert!(options.verbose);
+    }
+
+    #[test]
+    fn test_new_struct_generics() {
+        let pair = crate::macros::new_struct! {
+            Pair<'a, T: Copy + Into<u64>, U>
+            where
+                U: AsRef<str>,
+            {
+                name: &'a U = &"total",
+                values: [T; 2] = [1u8, 2],
+            }
+        };
+
+        let sum = pair.values.iter().copied().map(u64::from).sum::<u64>();
+        assert_eq!(sum, 3);
+        assert_eq!(*pair.name, "total");
+    }
+
+    #[test]
+    fn test_new_struct_bounds() {
+        let labeled = crate::macros::new_struct! {
+            Labeled<'a, 'b: 'a, T: ?Sized + std::fmt::Display, F: Fn(&T) -> String>
+            where
+                T: 'b,
+            {
+                value: &'b T = "awaur",
+                label: &'a str = "name",
+                format: F = |value| value.to_string(),
+            }
+        };
+
+        assert_eq!((labeled.format)(labeled.value), "awaur");
+        assert_eq!(labeled.label, "name");
+    }
+
+    #[test]
+    fn test_new_struct_tuple() {
+        let unit = crate::macros::new_struct! {
+            Unit(u8)
+        };
+
+        let pair = crate::macros::new_struct! {
+            Pair<T, F: Fn(T) -> T>(pub T = 2u32, F = |value| value * 3, Vec<T>)
+            where
+                T: Copy
+        };
+
+        assert_eq!(unit.0, 0);
+        assert_eq!((pair.1)(pair.0), 6);
+        assert!(pair.2.is_empty());
+    }
+
+    #[cfg(feature = "endpoints")]
+    #[test]
+    fn test_json_struct() {
+        use serde::Serialize;
+
+        let (body, json) = crate::macros::json_struct! {
+            #[derive(Serialize)]
+            NoteBody<'a> {
+                text: &'a str = "hello",
+                #[serde(skip_serializing_if = "Option::is_none")]
+                folder: Option<u32> = None,
+                tags: Vec<&'a str> = vec!["draft"],
+            }
+        };
+
+        assert_eq!(body.text, "hello");
+        assert_eq!(body.folder, None);
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "text": "hello",
+                "tags": ["draft"],
+            })
+        );
+        assert_eq!(serde_json::to_value(&body).unwrap(), json);
+    }
 }
@@ -0,0 +1,332 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use super::{PaginatedStream, PaginationDelegate, ReadyStateValue};
+
+/// A [`PaginationDelegate`] that can change the number of items it requests
+/// per page, for use with [`AdaptiveDelegate`].
+///
+/// This only makes sense for a delegate that requests pages by the offset of
+/// the first item (such as with an `offset` and a `limit` parameter), rather
+/// than by the number of the page, because the offset stays correct when the
+/// size of the pages changes.
+pub trait ResizablePaginationDelegate: PaginationDelegate {
+    /// Sets the number of items to request for each page after this. The
+    /// value returned by [`PaginationDelegate::page_size`] is expected to
+    /// change to match.
+    fn set_page_size(&mut self, value: usize);
+}
+
+/// Determines how an [`AdaptiveDelegate`] changes the size of pages, based on
+/// how long each page took to receive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptivePageSize {
+    min: usize,
+    max: usize,
+    target: Duration,
+}
+
+impl AdaptivePageSize {
+    /// Keep the size of pages between `min` and `max` (inclusive), aiming for
+    /// every page to take about `target` to receive.
+    ///
+    /// After a page that took longer than `target`, the size is halved. After
+    /// a page that took less than half of `target`, the size is doubled. The
+    /// size never leaves the bounds.
+    ///
+    /// # Panics
+    ///
+    /// If `min` is zero, or greater than `max`.
+    pub fn new(min: usize, max: usize, target: Duration) -> Self {
+        assert!(min > 0, "the minimum page size must not be zero");
+        assert!(
+            min <= max,
+            "the minimum page size must not exceed the maximum"
+        );
+
+        Self { min, max, target }
+    }
+
+    /// The size of the next page, given the `current` size, and the `latency`
+    /// of the page that was just received with it.
+    pub fn next_size(&self, current: usize, latency: Duration) -> usize {
+        let size = if latency > self.target {
+            current / 2
+        } else if latency < self.target / 2 {
+            current.saturating_mul(2)
+        } else {
+            current
+        };
+
+        size.clamp(self.min, self.max)
+    }
+}
+
+/// Wraps another [`ResizablePaginationDelegate`], measuring how long each call
+/// to [`PaginationDelegate::next_page`] takes, and changing the size of the
+/// next page according to an [`AdaptivePageSize`]. This trades the number of
+/// requests for the time that each one takes; pages get larger while the API
+/// is fast, and smaller when it starts to slow down.
+///
+/// The clock is a function that returns the current [`Instant`], which is
+/// [`Instant::now`] unless another is given with
+/// [`AdaptiveDelegate::with_clock`].
+///
+/// Use [`PaginatedStream::adaptive_page_size`] or
+/// [`PaginatedStream::adaptive_page_size_with`] rather than constructing this
+/// directly.
+pub struct AdaptiveDelegate<D, C = fn() -> Instant> {
+    inner: D,
+    policy: AdaptivePageSize,
+    clock: C,
+}
+
+impl<D> AdaptiveDelegate<D>
+where
+    D: ResizablePaginationDelegate,
+{
+    /// Wrap the `inner` delegate, resizing its pages according to `policy`.
+    /// The current size of the pages is clamped to the bounds of the policy
+    /// immediately, or set to the minimum if the delegate doesn't report one.
+    pub fn new(inner: D, policy: AdaptivePageSize) -> Self {
+        Self::with_clock(inner, policy, Instant::now)
+    }
+}
+
+impl<D, C> AdaptiveDelegate<D, C>
+where
+    D: ResizablePaginationDelegate,
+{
+    /// Same as [`AdaptiveDelegate::new`], but measuring how long each page
+    /// takes with the instants that `clock` returns.
+    pub fn with_clock(mut inner: D, policy: AdaptivePageSize, clock: C) -> Self {
+        let size = inner.page_size().unwrap_or(policy.min);
+        inner.set_page_size(size.clamp(policy.min, policy.max));
+
+        Self {
+            inner,
+            policy,
+            clock,
+        }
+    }
+}
+
+impl<D, C> AdaptiveDelegate<D, C> {
+    /// Reference to the inner delegate.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Consume this delegate, taking out the inner delegate.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<D, C> PaginationDelegate for AdaptiveDelegate<D, C>
+where
+    D: ResizablePaginationDelegate + Send,
+    D::Item: Send,
+    C: Fn() -> Instant + Send,
+{
+    type Item = D::Item;
+    type Error = D::Error;
+
+    async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+        let started = (self.clock)();
+        let items = self.inner.next_page().await?;
+        let latency = (self.clock)().saturating_duration_since(started);
+
+        // Only a page that was received successfully says anything about how
+        // fast the API is, so an error leaves the size as it was.
+        let current = self.inner.page_size().unwrap_or(self.policy.min);
+        self.inner
+            .set_page_size(self.policy.next_size(current, latency));
+
+        Ok(items)
+    }
+
+    fn offset(&self) -> usize {
+        self.inner.offset()
+    }
+
+    fn set_offset(&mut self, value: usize) {
+        self.inner.set_offset(value)
+    }
+
+    fn reset_offset(&mut self) {
+        self.inner.reset_offset()
+    }
+
+    fn total_items(&self) -> Option<usize> {
+        self.inner.total_items()
+    }
+
+    fn set_total_items(&mut self, value: usize) {
+        self.inner.set_total_items(value)
+    }
+
+    fn page_size(&self) -> Option<usize> {
+        self.inner.page_size()
+    }
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: ResizablePaginationDelegate + Send,
+    D::Item: Send,
+{
+    /// Change the number of items requested for each page between pages,
+    /// according to how long the previous page took to receive. See
+    /// [`AdaptivePageSize::new`] for how the size is chosen. The delegate must
+    /// implement [`ResizablePaginationDelegate`].
+    ///
+    /// The time is measured with [`Instant::now`]. To measure it with another
+    /// clock, see [`Self::adaptive_page_size_with`].
+    ///
+    /// # Panics
+    ///
+    /// This must not be called while a page is `Pending`, because the
+    /// delegate is owned by the future of that request.
+    pub fn adaptive_page_size(
+        self,
+        policy: AdaptivePageSize,
+    ) -> PaginatedStream<'f, AdaptiveDelegate<D>> {
+        self.adaptive_page_size_with(policy, Instant::now)
+    }
+
+    /// Same as [`Self::adaptive_page_size`], but measuring how long each page
+    /// takes with the instants that `clock` returns.
+    ///
+    /// # Panics
+    ///
+    /// This must not be called while a page is `Pending`, because the
+    /// delegate is owned by the future of that request.
+    pub fn adaptive_page_size_with<C>(
+        self,
+        policy: AdaptivePageSize,
+        clock: C,
+    ) -> PaginatedStream<'f, AdaptiveDelegate<D, C>>
+    where
+        C: Fn() -> Instant + Send,
+    {
+        use PaginatedStream::*;
+
+        match self {
+            Request(delegate) => Request(AdaptiveDelegate::with_clock(delegate, policy, clock)),
+            Ready(ReadyStateValue { delegate, items }) => Ready(ReadyStateValue {
+                delegate: AdaptiveDelegate::with_clock(delegate, policy, clock),
+                items,
+            }),
+            Closed => Closed,
+            Pending(_) | Indeterminate => {
+                panic!("cannot adapt the page size while a page request is pending")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use async_trait::async_trait;
+
+    use super::{AdaptivePageSize, ResizablePaginationDelegate};
+    use crate::paginator::{PaginatedStream, PaginationDelegate};
+
+    /// Pages over the numbers `0..total`, moving `clock` forward by the next
+    /// of `latencies` to respond to each page, and recording the size of every
+    /// page requested.
+    struct SlowDelegate {
+        offset: usize,
+        size: usize,
+        total: usize,
+        latencies: VecDeque<Duration>,
+        clock: Arc<Mutex<Instant>>,
+        sizes: Arc<Mutex<Vec<usize>>>,
+    }
+
+    #[async_trait]
+    impl PaginationDelegate for SlowDelegate {
+        type Item = usize;
+        type Error = ();
+
+        async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+            if let Some(latency) = self.latencies.pop_front() {
+                *self.clock.lock().unwrap() += latency;
+            }
+
+            self.sizes.lock().unwrap().push(self.size);
+
+            Ok((self.offset..self.total.min(self.offset + self.size)).collect())
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn set_offset(&mut self, value: usize) {
+            self.offset = value;
+        }
+
+        fn total_items(&self) -> Option<usize> {
+            Some(self.total)
+        }
+
+        fn page_size(&self) -> Option<usize> {
+            Some(self.size)
+        }
+    }
+
+    impl ResizablePaginationDelegate for SlowDelegate {
+        fn set_page_size(&mut self, value: usize) {
+            self.size = value;
+        }
+    }
+
+    #[test]
+    fn test_adaptive_page_size() {
+        let fast = Duration::ZERO;
+        let slow = Duration::from_millis(100);
+        let clock = Arc::new(Mutex::new(Instant::now()));
+        let sizes = Arc::new(Mutex::new(Vec::new()));
+        let delegate = SlowDelegate {
+            offset: 0,
+            size: 10,
+            total: 200,
+            latencies: [fast, fast, fast, slow, slow, slow, slow, fast].into(),
+            clock: clock.clone(),
+            sizes: sizes.clone(),
+        };
+        let stream = PaginatedStream::from(delegate).adaptive_page_size_with(
+            AdaptivePageSize::new(5, 32, Duration::from_millis(50)),
+            move || *clock.lock().unwrap(),
+        );
+
+        assert_eq!(
+            futures_lite::future::block_on(stream.try_collect_all()),
+            Ok((0..200).collect())
+        );
+
+        let sizes = sizes.lock().unwrap();
+        // Grows while fast, up to the maximum, then shrinks while slow, down
+        // to the minimum, and grows again once it is fast.
+        assert_eq!(sizes[..10], [10, 20, 32, 32, 16, 8, 5, 5, 10, 20]);
+        assert!(sizes.iter().all(|size| (5..=32).contains(size)));
+    }
+
+    #[test]
+    fn test_next_size() {
+        let policy = AdaptivePageSize::new(5, 32, Duration::from_millis(50));
+
+        assert_eq!(policy.next_size(10, Duration::ZERO), 20);
+        assert_eq!(policy.next_size(20, Duration::ZERO), 32);
+        assert_eq!(policy.next_size(20, Duration::from_millis(30)), 20);
+        assert_eq!(policy.next_size(8, Duration::from_millis(80)), 5);
+    }
+}
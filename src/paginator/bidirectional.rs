@@ -0,0 +1,189 @@
+use std::ops::Range;
+
+use async_trait::async_trait;
+
+use super::PaginationDelegate;
+
+/// A [`PaginationDelegate`] that can also request the page before the current
+/// offset, for use with [`BiDirectionalPaginator`].
+///
+/// Both methods are called after the offset has been set by the paginator.
+/// [`PaginationDelegate::next_page`] should return the page that starts at the
+/// offset, as it does for [`PaginatedStream`][super::PaginatedStream], and
+/// [`Self::prev_page`] should return the page that ends just before it.
+#[async_trait]
+pub trait BiDirectionalDelegate: PaginationDelegate {
+    /// Performs an asynchronous request for the page of items that ends just
+    /// before the current offset, in the same order as they would be returned
+    /// by `next_page`. For an API that takes an offset and a limit, this is
+    /// usually a request with the offset decreased by the page size (but not
+    /// below zero), and the limit reduced so that the page does not overlap
+    /// the current offset.
+    async fn prev_page(&mut self) -> Result<Vec<Self::Item>, Self::Error>;
+}
+
+/// Moves a cursor forward and backward through the pages of an API, fetching
+/// each page as it is moved to. Unlike
+/// [`PaginatedStream`][super::PaginatedStream], this yields whole pages rather
+/// than items, and does not buffer anything; moving back to a page requests it
+/// again.
+///
+/// The cursor is the range of offsets of the page that was returned last.
+/// Moving forward requests the page that starts at the end of that range, and
+/// moving backward requests the page that ends at the start of it, so that
+/// moving back and then forward again returns to the same page. If a request
+/// fails, the cursor does not move.
+pub struct BiDirectionalPaginator<D>
+where
+    D: BiDirectionalDelegate,
+{
+    delegate: D,
+    cursor: Range<usize>,
+}
+
+impl<D> From<D> for BiDirectionalPaginator<D>
+where
+    D: BiDirectionalDelegate,
+{
+    /// Start with the cursor at the current offset of the delegate, so that
+    /// the first call to [`BiDirectionalPaginator::next`] requests the page
+    /// that starts there.
+    fn from(other: D) -> Self {
+        let offset = other.offset();
+
+        Self {
+            delegate: other,
+            cursor: offset..offset,
+        }
+    }
+}
+
+impl<D> BiDirectionalPaginator<D>
+where
+    D: BiDirectionalDelegate,
+{
+    /// Move the cursor to the next page, and return its items. If the end of
+    /// the previous page has already reached the total, or the API returns an
+    /// empty page, this resolves to `None` without moving the cursor.
+    pub async fn next(&mut self) -> Result<Option<Vec<D::Item>>, D::Error> {
+        if self.cursor.end >= self.delegate.total_items().unwrap_or(usize::MAX) {
+            return Ok(None);
+        }
+
+        self.delegate.set_offset(self.cursor.end);
+
+        let mut items = self.delegate.next_page().await?;
+
+        // Never go past the latest total, in case it has decreased.
+        if let Some(total) = self.delegate.total_items() {
+            items.truncate(total.saturating_sub(self.cursor.end));
+        }
+
+        if items.is_empty() {
+            return Ok(None);
+        }
+
+        self.cursor = self.cursor.end..self.cursor.end + items.len();
+
+        Ok(Some(items))
+    }
+
+    /// Move the cursor to the previous page, and return its items. If the
+    /// cursor is already at the first page, or the API returns an empty page,
+    /// this resolves to `None` without moving the cursor.
+    pub async fn prev(&mut self) -> Result<Option<Vec<D::Item>>, D::Error> {
+        if self.cursor.start == 0 {
+            return Ok(None);
+        }
+
+        self.delegate.set_offset(self.cursor.start);
+
+        let mut items = self.delegate.prev_page().await?;
+
+        // A page that claims to be longer than the offset can't end there, so
+        // keep only the items that fit in front of it.
+        if items.len() > self.cursor.start {
+            items.drain(..items.len() - self.cursor.start);
+        }
+
+        if items.is_empty() {
+            return Ok(None);
+        }
+
+        self.cursor = self.cursor.start - items.len()..self.cursor.start;
+
+        Ok(Some(items))
+    }
+
+    /// The range of offsets of the page that was returned last. This is empty
+    /// before any page has been requested.
+    pub fn cursor(&self) -> Range<usize> {
+        self.cursor.clone()
+    }
+
+    /// Reference to the delegate.
+    pub fn delegate(&self) -> &D {
+        &self.delegate
+    }
+
+    /// Consume the paginator, taking out the delegate.
+    pub fn into_inner(self) -> D {
+        self.delegate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use super::{BiDirectionalDelegate, BiDirectionalPaginator};
+    use crate::paginator::tests::NumbersDelegate;
+    use crate::paginator::PaginationDelegate;
+
+    #[async_trait]
+    impl BiDirectionalDelegate for NumbersDelegate {
+        async fn prev_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+            if self.fail_at == Some(self.offset) {
+                return Err(self.offset);
+            }
+
+            self.offset = self.offset.saturating_sub(self.size);
+            let items = self.next_page().await;
+            // Leave the offset where it was, as `next_page` would.
+            self.offset += self.size;
+
+            items
+        }
+    }
+
+    #[test]
+    fn test_forward_and_back() {
+        let mut paginator = BiDirectionalPaginator::from(NumbersDelegate::new(3, 8));
+
+        futures_lite::future::block_on(async {
+            assert_eq!(paginator.prev().await, Ok(None));
+            assert_eq!(paginator.next().await, Ok(Some(vec![0, 1, 2])));
+            assert_eq!(paginator.next().await, Ok(Some(vec![3, 4, 5])));
+            assert_eq!(paginator.prev().await, Ok(Some(vec![0, 1, 2])));
+            assert_eq!(paginator.cursor(), 0..3);
+            assert_eq!(paginator.next().await, Ok(Some(vec![3, 4, 5])));
+            assert_eq!(paginator.next().await, Ok(Some(vec![6, 7])));
+            assert_eq!(paginator.next().await, Ok(None));
+            assert_eq!(paginator.cursor(), 6..8);
+            assert_eq!(paginator.prev().await, Ok(Some(vec![3, 4, 5])));
+        });
+    }
+
+    #[test]
+    fn test_error_keeps_cursor() {
+        let mut delegate = NumbersDelegate::new(3, 8);
+        delegate.fail_at = Some(3);
+        let mut paginator = BiDirectionalPaginator::from(delegate);
+
+        futures_lite::future::block_on(async {
+            assert_eq!(paginator.next().await, Ok(Some(vec![0, 1, 2])));
+            assert_eq!(paginator.next().await, Err(3));
+            assert_eq!(paginator.cursor(), 0..3);
+        });
+    }
+}
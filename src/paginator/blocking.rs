@@ -0,0 +1,88 @@
+use futures_core::Stream;
+use futures_lite::StreamExt;
+
+use super::{PaginatedStream, PaginationDelegate};
+
+/// An [`Iterator`] over the results of a [`PaginatedStream`], blocking the
+/// current thread until each one is ready. Construct this with
+/// [`PaginatedStream::into_blocking_iter`].
+pub struct BlockingIter<'f, D>
+where
+    D: PaginationDelegate,
+{
+    stream: PaginatedStream<'f, D>,
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    /// Turn this stream into an [`Iterator`], for synchronous code that has no
+    /// executor to poll it with. Every call to [`Iterator::next`] drives the
+    /// stream on the current thread with [`futures_lite::future::block_on`],
+    /// until it yields the next item or error.
+    ///
+    /// The futures of the delegate must not depend on a specific runtime,
+    /// such as the reactor of `tokio`, because none is running. This must not
+    /// be used from within asynchronous code, because it blocks the thread of
+    /// the executor that would otherwise be polling other tasks.
+    pub fn into_blocking_iter(self) -> BlockingIter<'f, D> {
+        BlockingIter { stream: self }
+    }
+}
+
+impl<'f, D> BlockingIter<'f, D>
+where
+    D: PaginationDelegate,
+{
+    /// Consume this iterator, taking out the stream that it was reading from.
+    pub fn into_inner(self) -> PaginatedStream<'f, D> {
+        self.stream
+    }
+}
+
+impl<'f, D> Iterator for BlockingIter<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    type Item = Result<D::Item, D::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        futures_lite::future::block_on(self.stream.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::paginator::tests::NumbersDelegate;
+    use crate::paginator::PaginatedStream;
+
+    #[test]
+    fn test_into_blocking_iter() {
+        let items = PaginatedStream::from(NumbersDelegate::new(4, 10))
+            .into_blocking_iter()
+            .collect::<Result<Vec<_>, _>>();
+
+        assert_eq!(items, Ok((0..10).collect()));
+    }
+
+    #[test]
+    fn test_into_blocking_iter_error() {
+        let mut delegate = NumbersDelegate::new(4, 12);
+        delegate.fail_at = Some(8);
+        let mut iter = PaginatedStream::from(delegate).into_blocking_iter();
+
+        assert_eq!(
+            iter.by_ref().take(8).collect::<Vec<_>>(),
+            (0..8).map(Ok).collect::<Vec<_>>()
+        );
+        assert_eq!(iter.next(), Some(Err(8)));
+        assert_eq!(iter.next(), None);
+    }
+}
@@ -0,0 +1,147 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use super::{PaginatedStream, PaginationDelegate};
+
+/// Yields only the items of a [`PaginatedStream`] that match a predicate, and
+/// stops after a number of them. Construct this with
+/// [`PaginatedStream::filter_take`].
+pub struct FilterTake<'f, D, F>
+where
+    D: PaginationDelegate,
+{
+    stream: PaginatedStream<'f, D>,
+    predicate: F,
+    remaining: usize,
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    /// Yield only the items for which `predicate` returns `true`, and no more
+    /// than `n` of them. This is for APIs that can't filter on the server,
+    /// where the items have to be filtered as they are received, but only a
+    /// few matches are needed.
+    ///
+    /// Pages are still requested one at a time, only once the items of the
+    /// previous page have been checked. After the `n`th match is yielded, the
+    /// stream is not polled again, so no page after the one containing that
+    /// match is ever requested. Errors are yielded as usual, and do not count
+    /// towards `n`.
+    ///
+    /// The offset of the delegate is kept correct throughout, so the stream can
+    /// be taken back out with [`FilterTake::into_inner`] to continue from the
+    /// item after the last one that was checked.
+    pub fn filter_take<F>(self, predicate: F, n: usize) -> FilterTake<'f, D, F>
+    where
+        F: FnMut(&D::Item) -> bool,
+    {
+        FilterTake {
+            stream: self,
+            predicate,
+            remaining: n,
+        }
+    }
+}
+
+impl<'f, D, F> FilterTake<'f, D, F>
+where
+    D: PaginationDelegate,
+{
+    /// The number of matches that may still be yielded.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Consume this, taking out the stream that is being filtered. Any items
+    /// of the current page that have not been checked yet are still in it.
+    pub fn into_inner(self) -> PaginatedStream<'f, D> {
+        self.stream
+    }
+}
+
+// The predicate is never pinned.
+impl<'f, D, F> Unpin for FilterTake<'f, D, F>
+where
+    D: PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+}
+
+impl<'f, D, F> Stream for FilterTake<'f, D, F>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+    F: FnMut(&D::Item) -> bool,
+{
+    type Item = Result<D::Item, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        while this.remaining > 0 {
+            match Pin::new(&mut this.stream).poll_next(ctx) {
+                Poll::Ready(Some(Ok(item))) => {
+                    if (this.predicate)(&item) {
+                        this.remaining -= 1;
+                        return Poll::Ready(Some(Ok(item)));
+                    }
+                }
+                poll => return poll,
+            }
+        }
+
+        Poll::Ready(None)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, upper) = self.stream.size_hint();
+
+        (
+            0,
+            Some(upper.map_or(self.remaining, |upper| upper.min(self.remaining))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::StreamExt;
+
+    use crate::paginator::tests::NumbersDelegate;
+    use crate::paginator::{PaginatedStream, ReadyStateValue};
+
+    #[test]
+    fn test_filter_take() {
+        let mut stream =
+            PaginatedStream::from(NumbersDelegate::new(4, 100)).filter_take(|n| n % 7 == 0, 3);
+
+        let items = futures_lite::future::block_on((&mut stream).collect::<Vec<_>>());
+        assert_eq!(items, [Ok(0), Ok(7), Ok(14)]);
+        assert_eq!(stream.remaining(), 0);
+
+        // The third match is on the page starting at 12, so that is the last
+        // page that was requested, and the rest of it is left unchecked.
+        match stream.into_inner() {
+            PaginatedStream::Ready(ReadyStateValue { delegate, items }) => {
+                assert_eq!(delegate.offset, 16);
+                assert_eq!(items, [15]);
+            }
+            _ => panic!("expected the last page to still be ready"),
+        }
+    }
+
+    #[test]
+    fn test_filter_take_exhausted() {
+        // There are only two matches in total, which is fewer than asked for.
+        let stream =
+            PaginatedStream::from(NumbersDelegate::new(5, 20)).filter_take(|n| n % 12 == 0, 3);
+
+        let items = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+        assert_eq!(items, [Ok(0), Ok(12)]);
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+
+use async_trait::async_trait;
+
+use super::PaginationDelegate;
+
+/// The error injected by a [`FixtureDelegate`], with the index of the page
+/// that it was injected at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixtureError {
+    /// The index of the page that failed, starting from zero.
+    pub page: usize,
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "injected error for the page at index {}", self.page)
+    }
+}
+
+impl std::error::Error for FixtureError {}
+
+/// A stand-in for a delegate that requests pages from an API, which instead
+/// paginates over a vector of items in memory. This is for testing code that
+/// consumes a [`PaginatedStream`], such as wrappers of an API, or
+/// combinators, without touching the network.
+///
+/// Every page has the same number of items, except for the last, and the
+/// total is the length of the vector. An error can be injected at any page
+/// with [`Self::with_error_at`], which fails every request for that page.
+///
+/// [`PaginatedStream`]: crate::paginator::PaginatedStream
+#[derive(Debug, Clone)]
+pub struct FixtureDelegate<T> {
+    items: Vec<T>,
+    page_size: usize,
+    offset: usize,
+    error_at: Option<usize>,
+    requests: usize,
+}
+
+impl<T> FixtureDelegate<T> {
+    /// Paginate over `items`, with `page_size` items on every page.
+    ///
+    /// # Panics
+    ///
+    /// If `page_size` is zero, because no page would ever have any items.
+    pub fn new(items: Vec<T>, page_size: usize) -> Self {
+        assert!(page_size > 0, "the page size must not be zero");
+
+        Self {
+            items,
+            page_size,
+            offset: 0,
+            error_at: None,
+            requests: 0,
+        }
+    }
+
+    /// Fail every request for the page at index `page`, starting from zero,
+    /// with a [`FixtureError`].
+    pub fn with_error_at(mut self, page: usize) -> Self {
+        self.error_at = Some(page);
+        self
+    }
+
+    /// The number of pages that have been requested so far, including any
+    /// that failed.
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+}
+
+#[async_trait]
+impl<T> PaginationDelegate for FixtureDelegate<T>
+where
+    T: Clone + Send,
+{
+    type Item = T;
+    type Error = FixtureError;
+
+    async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+        let page = self.offset / self.page_size;
+        self.requests += 1;
+
+        if self.error_at == Some(page) {
+            return Err(FixtureError { page });
+        }
+
+        let start = self.offset.min(self.items.len());
+        let end = (start + self.page_size).min(self.items.len());
+
+        Ok(self.items[start..end].to_vec())
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn set_offset(&mut self, value: usize) {
+        self.offset = value;
+    }
+
+    fn total_items(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+
+    fn page_size(&self) -> Option<usize> {
+        Some(self.page_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::StreamExt;
+
+    use super::{FixtureDelegate, FixtureError};
+    use crate::paginator::{PaginatedStream, ReadyStateValue};
+
+    fn letters() -> Vec<char> {
+        ('a'..='j').collect()
+    }
+
+    #[test]
+    fn test_fixture() {
+        let stream = PaginatedStream::from(FixtureDelegate::new(letters(), 3));
+
+        let items = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+        assert_eq!(items, letters().into_iter().map(Ok).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_fixture_take_items() {
+        let mut stream = PaginatedStream::from(FixtureDelegate::new(letters(), 3)).take_items(4);
+
+        let items = futures_lite::future::block_on((&mut stream).collect::<Vec<_>>());
+        assert_eq!(items, [Ok('a'), Ok('b'), Ok('c'), Ok('d')]);
+
+        // Only the page with the fourth item was requested after the first.
+        match stream.into_inner() {
+            PaginatedStream::Ready(ReadyStateValue { delegate, .. }) => {
+                assert_eq!(delegate.requests(), 2)
+            }
+            _ => panic!("expected the last page to still be ready"),
+        }
+    }
+
+    #[test]
+    fn test_fixture_filter_take() {
+        let stream = PaginatedStream::from(FixtureDelegate::new(letters(), 4))
+            .filter_take(|letter| "aeiou".contains(*letter), 3);
+
+        let items = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+        assert_eq!(items, [Ok('a'), Ok('e'), Ok('i')]);
+    }
+
+    #[test]
+    fn test_fixture_error() {
+        let stream = PaginatedStream::from(FixtureDelegate::new(letters(), 3).with_error_at(1));
+
+        let items = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+        assert_eq!(
+            items,
+            [Ok('a'), Ok('b'), Ok('c'), Err(FixtureError { page: 1 })]
+        );
+    }
+}
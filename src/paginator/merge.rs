@@ -0,0 +1,247 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use super::{PaginatedStream, PaginationDelegate};
+
+/// Merges the items of two [`PaginatedStream`]s that are both sorted by the
+/// same key, yielding them in order of that key, and yielding each key only
+/// once. Construct this with [`PaginatedStream::merge_by_key`].
+///
+/// When both streams yield an item with the same key, the item from the
+/// primary stream (the one that `merge_by_key` was called on) is yielded, and
+/// the other is dropped. If either stream yields an error, that error is
+/// yielded once and the merged stream is closed.
+pub struct MergeByKey<'f, P, S, K, F>
+where
+    P: PaginationDelegate,
+    S: PaginationDelegate<Item = P::Item, Error = P::Error>,
+{
+    primary: Source<'f, P>,
+    secondary: Source<'f, S>,
+    key: F,
+    last_key: Option<K>,
+    closed: bool,
+}
+
+/// One of the two streams being merged, and the next item from it, which is
+/// held until it is the lowest of the two.
+struct Source<'f, D: PaginationDelegate> {
+    stream: PaginatedStream<'f, D>,
+    head: Option<D::Item>,
+    done: bool,
+}
+
+impl<'f, D> Source<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    /// Poll the stream for the next item, unless one is already held. Resolves
+    /// when there is an item held, or the stream is done.
+    fn poll_head(&mut self, ctx: &mut Context<'_>) -> Poll<Result<(), D::Error>> {
+        if self.head.is_some() || self.done {
+            return Poll::Ready(Ok(()));
+        }
+
+        match Pin::new(&mut self.stream).poll_next(ctx) {
+            Poll::Ready(Some(Ok(item))) => self.head = Some(item),
+            Poll::Ready(Some(Err(error))) => return Poll::Ready(Err(error)),
+            Poll::Ready(None) => self.done = true,
+            Poll::Pending => return Poll::Pending,
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    /// Merge this stream with `other`, both of which **must already be sorted
+    /// in ascending order** by the key that `key` returns for each item. Items
+    /// are yielded in the order of that key, and any item with the same key as
+    /// the one yielded before it is dropped, so that each key is yielded only
+    /// once. Items from this stream win over items from `other`.
+    ///
+    /// If either stream is not sorted, the merged stream is not sorted either,
+    /// and duplicates are only dropped when they happen to be adjacent.
+    pub fn merge_by_key<S, K, F>(
+        self,
+        other: PaginatedStream<'f, S>,
+        key: F,
+    ) -> MergeByKey<'f, D, S, K, F>
+    where
+        S: 'f + PaginationDelegate<Item = D::Item, Error = D::Error> + Unpin,
+        K: Ord,
+        F: FnMut(&D::Item) -> K,
+    {
+        MergeByKey {
+            primary: Source {
+                stream: self,
+                head: None,
+                done: false,
+            },
+            secondary: Source {
+                stream: other,
+                head: None,
+                done: false,
+            },
+            key,
+            last_key: None,
+            closed: false,
+        }
+    }
+}
+
+// Neither the key function nor the last key are ever pinned.
+impl<'f, P, S, K, F> Unpin for MergeByKey<'f, P, S, K, F>
+where
+    P: PaginationDelegate + Unpin,
+    P::Item: Unpin,
+    S: PaginationDelegate<Item = P::Item, Error = P::Error> + Unpin,
+{
+}
+
+impl<'f, P, S, K, F> Stream for MergeByKey<'f, P, S, K, F>
+where
+    P: 'f + PaginationDelegate + Unpin,
+    P::Item: Unpin,
+    S: 'f + PaginationDelegate<Item = P::Item, Error = P::Error> + Unpin,
+    K: Ord,
+    F: FnMut(&P::Item) -> K,
+{
+    type Item = Result<P::Item, P::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.closed {
+                return Poll::Ready(None);
+            }
+
+            // Both streams must have an item ready (or be done) before it can
+            // be known which item is next.
+            let primary = this.primary.poll_head(ctx);
+            let secondary = this.secondary.poll_head(ctx);
+
+            match (primary, secondary) {
+                (Poll::Ready(Err(error)), _) | (_, Poll::Ready(Err(error))) => {
+                    this.closed = true;
+                    return Poll::Ready(Some(Err(error)));
+                }
+                (Poll::Pending, _) | (_, Poll::Pending) => return Poll::Pending,
+                _ => (),
+            }
+
+            let primary_key = this.primary.head.as_ref().map(&mut this.key);
+            let secondary_key = this.secondary.head.as_ref().map(&mut this.key);
+
+            let (item, key) = match (primary_key, secondary_key) {
+                (None, None) => {
+                    this.closed = true;
+                    return Poll::Ready(None);
+                }
+                (Some(primary_key), Some(secondary_key)) if secondary_key < primary_key => {
+                    (this.secondary.head.take(), secondary_key)
+                }
+                (Some(primary_key), _) => (this.primary.head.take(), primary_key),
+                (None, Some(secondary_key)) => (this.secondary.head.take(), secondary_key),
+            };
+
+            if this.last_key.as_ref() == Some(&key) {
+                continue;
+            }
+
+            this.last_key = Some(key);
+
+            // Use of unwrap:
+            // The key was computed from the item that was just taken.
+            return Poll::Ready(Some(Ok(item.unwrap())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use futures_lite::StreamExt;
+
+    use crate::paginator::{PaginatedStream, PaginationDelegate};
+
+    /// Serves the given pages in order.
+    struct PagesDelegate {
+        pages: Vec<Vec<(u32, &'static str)>>,
+        offset: usize,
+        total: usize,
+    }
+
+    impl PagesDelegate {
+        fn new(pages: Vec<Vec<(u32, &'static str)>>) -> Self {
+            let total = pages.iter().map(Vec::len).sum();
+            Self {
+                pages,
+                offset: 0,
+                total,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl PaginationDelegate for PagesDelegate {
+        type Item = (u32, &'static str);
+        type Error = ();
+
+        async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+            Ok(self.pages.remove(0))
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn set_offset(&mut self, value: usize) {
+            self.offset = value;
+        }
+
+        fn total_items(&self) -> Option<usize> {
+            Some(self.total)
+        }
+    }
+
+    #[test]
+    fn test_merge_by_key() {
+        let primary = PaginatedStream::from(PagesDelegate::new(vec![
+            vec![(1, "primary"), (3, "primary")],
+            vec![(4, "primary"), (7, "primary")],
+        ]));
+        let replica = PaginatedStream::from(PagesDelegate::new(vec![
+            vec![(2, "replica"), (3, "replica"), (4, "replica")],
+            vec![(5, "replica"), (8, "replica")],
+        ]));
+
+        let merged = futures_lite::future::block_on(
+            primary
+                .merge_by_key(replica, |(id, _)| *id)
+                .map(Result::unwrap)
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(
+            merged,
+            [
+                (1, "primary"),
+                (2, "replica"),
+                (3, "primary"),
+                (4, "primary"),
+                (5, "replica"),
+                (7, "primary"),
+                (8, "replica"),
+            ]
+        );
+    }
+}
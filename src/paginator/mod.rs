@@ -0,0 +1,913 @@
+//! This module helps you implement pagination over a web API endpoint.
+//!
+//! The only thing that needs to happen for your functions to return
+//! asynchronous paginators (via the [`Stream`] trait) is the implementation of
+//! the [`PaginationDelegate`] trait. See the documentation of the methods on
+//! that trait to see what they should do.
+
+pub(crate) mod adaptive;
+pub(crate) mod bidirectional;
+#[cfg(feature = "blocking")]
+pub(crate) mod blocking;
+pub(crate) mod filter;
+#[cfg(feature = "testing")]
+pub(crate) mod fixture;
+pub(crate) mod merge;
+pub(crate) mod pages;
+pub(crate) mod prefetch;
+pub(crate) mod retry;
+pub(crate) mod snapshot;
+pub(crate) mod split;
+pub(crate) mod streaming;
+pub(crate) mod take;
+#[cfg(feature = "endpoints")]
+pub(crate) mod throttle;
+pub(crate) mod timeout;
+pub(crate) mod unfold;
+pub(crate) mod windows;
+
+pub use adaptive::*;
+pub use bidirectional::*;
+#[cfg(feature = "blocking")]
+pub use blocking::*;
+pub use filter::*;
+#[cfg(feature = "testing")]
+pub use fixture::*;
+pub use merge::*;
+pub use pages::*;
+pub use prefetch::*;
+pub use retry::*;
+pub use snapshot::*;
+pub use split::*;
+pub use streaming::*;
+pub use take::*;
+#[cfg(feature = "endpoints")]
+pub use throttle::*;
+pub use timeout::*;
+pub use unfold::*;
+pub use windows::*;
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use futures_core::{Future, Stream};
+
+/// This is the trait that needs to be implemented in order to tell the
+/// [`PaginatedStream`] how to keep track of the current page and make requests
+/// to the API.
+///
+/// The indices of the pages are handled automatically, simply
+/// implement `offset` and `set_offset` correctly to ensure that any internal
+/// fields are being updated.
+///
+/// After creating implementing this on a type, use `PaginatedStream::from` to
+/// get an iterable stream from the delegate.
+#[async_trait]
+pub trait PaginationDelegate {
+    /// This is the type of the item that calls to `poll_next` are expected to
+    /// yield.
+    type Item;
+    /// This is the type error that will occur when a future from
+    /// `PaginationDelegate::next_page` resolves to an error.
+    type Error;
+
+    /// Performs an asynchronous request for the next page and returns either
+    /// a vector of the result items or an error. Implementing this may require
+    /// the [`macro@async_trait`] macro from the [mod@async_trait] crate.
+    async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error>;
+
+    /// Gets the current offset, which will be the index at the end of the
+    /// current/previous page. The value returned from this will be changed by
+    /// [`PaginatedStream`] immediately following a successful call to
+    /// [`Self::next_page`], increasing by the number of items returned.
+    fn offset(&self) -> usize;
+
+    /// Sets the offset for the next page. The offset is required to be the
+    /// index of the last item from the previous page.
+    ///
+    /// After this is called, [`Self::offset`] must return `value`, or at least
+    /// something greater than the offset before it, unless the page was empty.
+    /// In debug builds, [`PaginatedStream`] panics if the offset does not
+    /// advance after a page with items, because the same page would otherwise
+    /// be requested over and over again.
+    fn set_offset(&mut self, value: usize);
+
+    /// Sets the offset back to the start of the collection, so that the next
+    /// page requested is the first. This is used by [`PaginatedStream::reset`].
+    /// The default implementation calls [`Self::set_offset`] with zero, which
+    /// should be overridden if the first page is somewhere else, or if there
+    /// is other state, such as a cursor, that must be cleared too.
+    fn reset_offset(&mut self) {
+        self.set_offset(0);
+    }
+
+    /// Gets the total count of items that are currently expected from the API.
+    /// This may change if the API returns a different number of results on
+    /// subsequent pages, and may be less than what the API claims in its
+    /// response data if the API has a maximum limit and stops providing results
+    /// after a certain amount.
+    ///
+    /// If the total decreases, any items that have already been yielded are
+    /// not taken back, but [`PaginatedStream`] will not yield any more items
+    /// than the latest total. Items of a newly received page that would exceed
+    /// it are dropped, and the stream is closed after the rest are yielded.
+    fn total_items(&self) -> Option<usize>;
+
+    /// Sets the total count of items, for when it was learned without the
+    /// delegate, such as from a page that was requested before the stream was
+    /// created with [`PaginatedStream::with_initial_page`]. After this is
+    /// called, [`Self::total_items`] should return `Some(value)`.
+    ///
+    /// The default implementation does nothing, which is only correct for a
+    /// delegate that can't store the total, or already knows it.
+    fn set_total_items(&mut self, value: usize) {
+        let _ = value;
+    }
+
+    /// Gets the number of items that the API returns for every page except the
+    /// last. This is only needed for [`PrefetchPolicy::Concurrent`], which uses
+    /// it to compute the offsets of pages that are requested ahead of time. The
+    /// default implementation returns `None`, meaning that the size is unknown
+    /// or may vary.
+    fn page_size(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Resolution type of the future from [`PaginatedStream::Pending`] and the
+/// inner value of [`PaginatedStream::Ready`].
+pub struct ReadyStateValue<D>
+where
+    D: PaginationDelegate,
+{
+    delegate: D,
+    items: VecDeque<D::Item>,
+}
+
+/// The future will be the result returned from the
+/// [`PaginationDelegate::next_page`], and will either resolve to an `Err` with
+/// `<D as PaginationDelegate>::Error` or a [`PendingFutureOutput`] with the
+/// delegate and response items.
+pub type PendingStateFuture<'f, D> =
+    dyn Future<Output = Result<ReadyStateValue<D>, <D as PaginationDelegate>::Error>> + 'f;
+
+/// This enumerable holds the current state of the paginated stream and also
+/// implements the [`Stream`] trait itself. It is highly recommended to read the
+/// source code of the `Stream` implementation for more documentation about how
+/// the state is changed as the stream is polled, there is a liberal amount of
+/// commentary.
+pub enum PaginatedStream<'f, D: PaginationDelegate> {
+    /// This is the entry-point, or rather where the state machine begins.
+    /// This is also used to indicate that the state machine is ready for the
+    /// next page from the API. This will be set when the state was previously
+    /// `Ready` but had no more items to yield.
+    Request(D),
+    /// At some point in the past, the delegate was requested to fetch the next
+    /// page and has returned a future. This will be polled whenever `poll_next`
+    /// is called, eventually resulting in the state changing to `Ready` if
+    /// successful, or `Closed` if an error was yielded.
+    Pending(Pin<Box<PendingStateFuture<'f, D>>>),
+    /// The next page is ready and its current items have been taken and are
+    /// currently being yielded to whatever is polling the stream. This state
+    /// will remain the same until it runs out of items, and on the very next
+    /// poll, the state will change back to `Request` if there is another page,
+    /// or `Closed` if the expected number of results has already been yielded.
+    Ready(ReadyStateValue<D>),
+    /// Either an error has occurred or the API has been exhausted of the items
+    /// that it is willing to provide. Polling the stream when this is the state
+    /// will always yield `Poll::Ready(None)`, and will never change once this
+    /// has been set.
+    Closed,
+    /// This state is used internally when the result of `poll_next` is being
+    /// resolved. If you are matching variants directly, always resolve this
+    /// to [`unimplemented!()`].
+    Indeterminate,
+}
+
+impl<'f, D> From<D> for PaginatedStream<'f, D>
+where
+    D: PaginationDelegate,
+{
+    fn from(other: D) -> PaginatedStream<'f, D> {
+        PaginatedStream::Request(other)
+    }
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: PaginationDelegate,
+{
+    /// Create a stream that starts with `items`, the page at the current offset
+    /// of the `delegate`, which has already been requested by other means,
+    /// such as to inspect the total before deciding to paginate. The first
+    /// polls yield these items without making a request, and the next page is
+    /// requested from the offset after them, as if the stream had requested
+    /// the first page itself.
+    ///
+    /// The delegate is not given the chance to read the total from the
+    /// response of that page, so if it is known, it should be given as
+    /// `total`, which is set on the delegate with
+    /// [`PaginationDelegate::set_total_items`], so that the size hint is right
+    /// from the start. The items are truncated to the total, and if there are
+    /// none, the stream starts `Closed`.
+    pub fn with_initial_page(mut delegate: D, items: Vec<D::Item>, total: Option<usize>) -> Self {
+        let mut items = VecDeque::from(items);
+
+        if let Some(total) = total {
+            delegate.set_total_items(total);
+        }
+
+        if let Some(total) = total.or_else(|| delegate.total_items()) {
+            items.truncate(total.saturating_sub(delegate.offset()));
+        }
+
+        if items.is_empty() {
+            return PaginatedStream::Closed;
+        }
+
+        advance_offset(&mut delegate, items.len());
+
+        PaginatedStream::Ready(ReadyStateValue { delegate, items })
+    }
+
+    /// Consume the stream, taking out the delegate, so that it can be used to
+    /// build another stream. Any items of the current page that have not been
+    /// yielded yet are discarded, but the offset of the delegate is left as it
+    /// is.
+    ///
+    /// This is `None` if the state is `Pending`, because the delegate is owned
+    /// by the future of that request, or `Closed`, because the delegate is
+    /// dropped once the stream is exhausted or yields an error.
+    pub fn into_delegate(self) -> Option<D> {
+        match self {
+            PaginatedStream::Request(delegate)
+            | PaginatedStream::Ready(ReadyStateValue { delegate, .. }) => Some(delegate),
+            _ => None,
+        }
+    }
+
+    /// Reference to the delegate, unless it is owned by the future of a
+    /// request, or the stream is closed.
+    fn delegate(&self) -> Option<&D> {
+        match self {
+            PaginatedStream::Request(delegate)
+            | PaginatedStream::Ready(ReadyStateValue { delegate, .. }) => Some(delegate),
+            _ => None,
+        }
+    }
+
+    /// Start the stream over from the first page, by setting the offset of
+    /// the delegate to zero and discarding any items of the current page that
+    /// have not been yielded yet. The next poll will request the first page
+    /// again. This is for re-scanning an endpoint periodically without
+    /// constructing the delegate again.
+    ///
+    /// Returns `false` and leaves the stream unchanged if there is no delegate
+    /// to reset, which is the case for the same states that
+    /// [`Self::into_delegate`] returns `None` for. To be able to start over
+    /// after the stream has closed, take the delegate out with that method
+    /// before the last item is yielded, or keep what is needed to construct
+    /// the delegate again.
+    pub fn reset(&mut self) -> bool {
+        use PaginatedStream::*;
+
+        match std::mem::replace(self, Indeterminate) {
+            Request(mut delegate) | Ready(ReadyStateValue { mut delegate, .. }) => {
+                delegate.reset_offset();
+                *self = Request(delegate);
+                true
+            }
+            other => {
+                *self = other;
+                false
+            }
+        }
+    }
+}
+
+/// Move the offset of the `delegate` past the `count` items that were just
+/// received. In debug builds, this checks that the delegate actually reports
+/// the new offset, because one that doesn't advance would have the same page
+/// requested forever.
+pub(crate) fn advance_offset<D>(delegate: &mut D, count: usize)
+where
+    D: PaginationDelegate + ?Sized,
+{
+    let offset = delegate.offset();
+    delegate.set_offset(offset + count);
+
+    debug_assert!(
+        count == 0 || delegate.offset() > offset,
+        "the offset of the pagination delegate did not advance past {} after a page of {} \
+         items was received; `PaginationDelegate::offset` must return at least the value last \
+         given to `PaginationDelegate::set_offset`",
+        offset,
+        count,
+    );
+}
+
+impl<'f, D> Stream for PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    // If the state is `Pending` and the future resolves to an `Err`, that error is
+    // forwarded only once and the state set to `Closed`. If there is at least one
+    // result to return, the `Ok` variant is, of course, used instead.
+    type Item = Result<D::Item, D::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Avoid using the full namespace to match all variants.
+        use PaginatedStream::*;
+
+        // Take ownership of the current state (`self`) and replace it with the
+        // `Indeterminate` state until the new state is in fact determined.
+        let this = std::mem::replace(&mut *self, Indeterminate);
+
+        match this {
+            // This state occurs at the entry of the state machine and when there was a poll when
+            // the state was `Ready` but had no items to yield. It only holds the
+            // `PaginationDelegate` that will be used to update the offset and make new requests.
+            Request(mut delegate) => {
+                self.set(Pending(Box::pin(async {
+                    // Request the next page from the delegate and await the result.
+                    let result = delegate.next_page().await;
+                    // Map the `Ok` value of the result to a tuple that includes the delegate
+                    // that was moved into this block.
+                    result.map(|items| ReadyStateValue {
+                        delegate,
+                        items: items.into_iter().collect(),
+                    })
+                })));
+
+                // Reawaken the context so that the executor doesn't ignore the future.
+                ctx.waker().wake_by_ref();
+
+                // Return the distilled version of the new state to the callee, indicating that
+                // a new request has been made, and we are waiting for new data.
+                Poll::Pending
+            }
+            // At some point in the past this stream was polled and asked the delegate to make a new
+            // request. Now it is time to poll the future returned from that request, and if results
+            // are available, unpack them to the `Ready` state and move the delegate. If the future
+            // still doesn't have results, set the state back to `Pending` and move the fields back
+            // into position.
+            Pending(mut future) => match future.as_mut().poll(ctx) {
+                // The future from the last request returned successfully with new items,
+                // and gave the delegate back.
+                Poll::Ready(Ok(ReadyStateValue {
+                    mut delegate,
+                    mut items,
+                })) => {
+                    // If the API now reports fewer items in total than the offset of this page
+                    // plus its length, drop the items that exceed the new total. This ensures
+                    // that no more than the latest reported total is ever yielded.
+                    if let Some(total) = delegate.total_items() {
+                        items.truncate(total.saturating_sub(delegate.offset()));
+                    }
+
+                    // Tell the delegate the offset for the next page, which is the sum of the
+                    // old offset and the number of items that the API sent back.
+                    advance_offset(&mut delegate, items.len());
+
+                    // Get the first item out so that it can be yielded.
+                    match items.pop_front() {
+                        Some(popped) => {
+                            // Set the new state to `Ready` with the delegate and the items.
+                            self.set(Ready(ReadyStateValue { delegate, items }));
+
+                            // Note that this could have been `self.poll_next(ctx)` rather than
+                            // popping the item in this branch, but doing everything here is
+                            // better than moving the fields twice and doing unnecessary checks.
+                            Poll::Ready(Some(Ok(popped)))
+                        }
+                        // The page was empty, or every item of it was beyond the total. Either
+                        // way, the API has nothing more to give, so close the stream.
+                        None => {
+                            self.set(Closed);
+                            Poll::Ready(None)
+                        }
+                    }
+                }
+                // The future from the last request returned with an error.
+                Poll::Ready(Err(error)) => {
+                    // Set the state to `Closed` so that any future polls will return
+                    // `Poll::Ready(None)`. The callee can even match against this if needed.
+                    self.set(Closed);
+
+                    // Forward the error to whoever polled. This will only happen once because the
+                    // error is moved, and the state set to `Closed`.
+                    Poll::Ready(Some(Err(error)))
+                }
+                // The future from the last request is still pending.
+                Poll::Pending => {
+                    // Because the state is currently `Indeterminate` it must be set back to what it
+                    // was. This will move the future back into the state.
+                    self.set(Pending(future));
+
+                    // Tell the callee that we are still waiting for a response.
+                    Poll::Pending
+                }
+            },
+            // The request has resolved with data in the past, and there are items ready for us to
+            // provide the callee. In the event that there are no more items in the `VecDeque`, we
+            // will make the next request and construct the state for `Pending` again.
+            Ready(ReadyStateValue {
+                delegate,
+                mut items,
+            }) => match items.pop_front() {
+                // There is at least one item in the buffer, so yield it.
+                Some(item) => {
+                    // Set the state back to `Ready`, even if the items buffer is empty. This allows
+                    // the next page request to be made lazily, only after the current page is
+                    // exhausted, and then the stream is polled again.
+                    self.set(Ready(ReadyStateValue { delegate, items }));
+                    Poll::Ready(Some(Ok(item)))
+                }
+                // There was no item to yield.
+                None => {
+                    // Check if we have met or exceeded the number of items expected to be yielded.
+                    // Unwrapping `delegate.total_items()` should be safe because it would be
+                    // impossible to be in the `Ready` state if we have not received data from the
+                    // API yet, which is the only situation in which the value here would be `None`.
+                    if delegate.offset() >= delegate.total_items().unwrap_or(usize::MAX) {
+                        // All the items that API is willing to send have been yielded, so set
+                        // the stream to `Closed` so that any further polls will yield
+                        // `Poll::Ready(None)`.
+                        self.set(Closed);
+                        Poll::Ready(None)
+                    } else {
+                        // Set the state back to `Request` so that the next poll will make a request
+                        // for the next page. The offset should have already been updated at a
+                        // previous state.
+                        self.set(Request(delegate));
+                        // Poll again to make the request and forward the `Poll::Pending`.
+                        self.poll_next(ctx)
+                    }
+                }
+            },
+            // Either an error has occurred, or the last item has been yielded already. Nobody
+            // should be polling anymore, but to be nice, just tell them that there are no more
+            // results with `Poll::Ready(None)`.
+            Closed => {
+                // The state was replaced with `Indeterminate` above, so it must be set back.
+                self.set(Closed);
+                Poll::Ready(None)
+            }
+            // The `Indeterminate` state should have only been used internally and reset back to a
+            // valid state before yielding the `Poll` to the callee. This branch should never be
+            // reached, if it is, that is a panic.
+            Indeterminate => unreachable!(),
+        }
+    }
+
+    /// Currently, it is only possible to get the bounds from the `Request` and
+    /// `Ready` state. If no request has been made yet, the delegate can't know
+    /// the expected number of items and will therefore return `None`. It
+    /// should be possible to get a value when the state is `Pending`, but
+    /// unfortunately the delegate is locked behind the stack frame of the
+    /// pinned `Future`.
+    ///
+    /// The lower bound counts the items of the current page that have not been
+    /// yielded yet, and if the total is known, the items after the offset of
+    /// the delegate that have not been requested yet. The upper bound is the
+    /// total less the items that have already been yielded, which is the same
+    /// unless the total changes between pages. An error will end the stream
+    /// before that many items have been yielded.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        use PaginatedStream::*;
+
+        let (delegate, buffered) = match self {
+            Request(delegate) => (delegate, 0),
+            Ready(ReadyStateValue { delegate, items }) => (delegate, items.len()),
+            _ => return (0, None),
+        };
+        let total = delegate.total_items();
+        let lower = buffered + total.map_or(0, |total| total.saturating_sub(delegate.offset()));
+        let yielded = delegate.offset().saturating_sub(buffered);
+
+        (
+            lower,
+            total.map(|total| total.saturating_sub(yielded).max(lower)),
+        )
+    }
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    /// Poll the stream for the next item, with the [`Result`] of it moved to
+    /// the outside. This resolves to `Ok(None)` once the stream has ended.
+    ///
+    /// Because the stream yields a `Result` for every item, it is also a
+    /// `TryStream`, and works with every combinator of `TryStreamExt` from the
+    /// `futures` crate. This is the same as its `try_next`, for when nothing
+    /// else is needed from that crate. After an error, the stream is `Closed`,
+    /// so the error is returned only once, and `Ok(None)` every time after.
+    pub async fn try_next(&mut self) -> Result<Option<D::Item>, D::Error> {
+        futures_lite::StreamExt::next(self).await.transpose()
+    }
+
+    /// Poll the stream until it is exhausted, counting the items that are
+    /// yielded without keeping any of them in memory. If an error is yielded,
+    /// this stops polling and resolves to that error.
+    pub async fn try_count(mut self) -> Result<usize, D::Error> {
+        let mut count = 0;
+
+        while let Some(item) = futures_lite::StreamExt::next(&mut self).await {
+            item?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Poll the stream until it is exhausted, collecting every item that is
+    /// yielded into a vector. If an error is yielded, this stops polling and
+    /// resolves to that error. The vector is allocated up front with the
+    /// lower bound of [`Stream::size_hint`], up to 1024 items, and grows from
+    /// there, so that a total reported by the server can't cause an allocation
+    /// of any size.
+    ///
+    /// Because a stream is `Closed` after yielding an error, calling this on a
+    /// stream that has already yielded an error resolves to an empty vector,
+    /// rather than reporting the same error again.
+    pub async fn try_collect_all(mut self) -> Result<Vec<D::Item>, D::Error> {
+        let mut items = Vec::with_capacity(self.size_hint().0.min(1024));
+
+        while let Some(item) = futures_lite::StreamExt::next(&mut self).await {
+            items.push(item?);
+        }
+
+        Ok(items)
+    }
+
+    /// Same as [`Self::try_collect_all`], but calling `progress` after every
+    /// item with the number of items collected so far, and the total that the
+    /// delegate expects, if it knows it. This is for long scans, such as in an
+    /// application with a progress bar, that may be cancelled partway.
+    ///
+    /// Once the `cancel` future resolves, no more items are collected, and
+    /// this resolves to those that have been collected so far, without an
+    /// error. The future is polled before each item, so a scan that has been
+    /// cancelled stops right away, even if the next item is already buffered.
+    /// Pass [`futures_lite::future::pending`] to never cancel.
+    pub async fn collect_with<P, C>(
+        mut self,
+        mut progress: P,
+        cancel: C,
+    ) -> Result<Vec<D::Item>, D::Error>
+    where
+        P: FnMut(usize, Option<usize>),
+        C: Future,
+    {
+        futures_lite::pin!(cancel);
+        let mut items = Vec::new();
+
+        loop {
+            let next = futures_lite::future::or(
+                async {
+                    (&mut cancel).await;
+                    None
+                },
+                async { Some(futures_lite::StreamExt::next(&mut self).await) },
+            )
+            .await;
+
+            match next {
+                Some(Some(item)) => items.push(item?),
+                // The stream has ended, or the scan was cancelled.
+                Some(None) | None => return Ok(items),
+            }
+
+            progress(
+                items.len(),
+                self.delegate().and_then(PaginationDelegate::total_items),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use super::{PaginatedStream, PaginationDelegate, ReadyStateValue};
+
+    /// Pages over the numbers `0..total`, `size` at a time, failing on the
+    /// page that starts at `fail_at`, if any.
+    pub(super) struct NumbersDelegate {
+        pub offset: usize,
+        pub size: usize,
+        pub total: usize,
+        pub fail_at: Option<usize>,
+        /// The number of times to fail on the page at `fail_at` before it
+        /// succeeds, or `None` to always fail.
+        pub fail_times: Option<usize>,
+        /// Reported as the total after every page but the first, regardless of
+        /// the items that those pages contain.
+        pub shrink_to: Option<usize>,
+    }
+
+    impl NumbersDelegate {
+        pub fn new(size: usize, total: usize) -> Self {
+            Self {
+                offset: 0,
+                size,
+                total,
+                fail_at: None,
+                fail_times: None,
+                shrink_to: None,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl PaginationDelegate for NumbersDelegate {
+        type Item = usize;
+        type Error = usize;
+
+        async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+            if self.fail_at == Some(self.offset) {
+                match &mut self.fail_times {
+                    Some(0) => (),
+                    Some(times) => {
+                        *times -= 1;
+                        return Err(self.offset);
+                    }
+                    None => return Err(self.offset),
+                }
+            }
+
+            let items = (self.offset..self.total.min(self.offset + self.size)).collect();
+
+            if let (Some(total), true) = (self.shrink_to, self.offset > 0) {
+                self.total = total;
+            }
+
+            Ok(items)
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn set_offset(&mut self, value: usize) {
+            self.offset = value;
+        }
+
+        fn total_items(&self) -> Option<usize> {
+            Some(self.total)
+        }
+
+        fn set_total_items(&mut self, value: usize) {
+            self.total = value;
+        }
+    }
+
+    #[test]
+    fn test_try_next() {
+        let mut delegate = NumbersDelegate::new(2, 10);
+        delegate.fail_at = Some(2);
+        let mut stream = PaginatedStream::from(delegate);
+
+        futures_lite::future::block_on(async {
+            assert_eq!(stream.try_next().await, Ok(Some(0)));
+            assert_eq!(stream.try_next().await, Ok(Some(1)));
+            assert_eq!(stream.try_next().await, Err(2));
+            assert_eq!(stream.try_next().await, Ok(None));
+            assert_eq!(stream.try_next().await, Ok(None));
+        });
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut stream = PaginatedStream::from(NumbersDelegate::new(4, 10));
+
+        futures_lite::future::block_on(async {
+            assert_eq!(stream.try_next().await, Ok(Some(0)));
+            assert_eq!(stream.try_next().await, Ok(Some(1)));
+
+            assert!(stream.reset());
+            assert_eq!(stream.try_next().await, Ok(Some(0)));
+
+            // Once the stream has closed, there is no delegate left to reset.
+            while stream.try_next().await.unwrap().is_some() {}
+            assert!(!stream.reset());
+            assert!(matches!(stream, PaginatedStream::Closed));
+        });
+    }
+
+    #[test]
+    fn test_into_delegate() {
+        let mut stream = PaginatedStream::from(NumbersDelegate::new(4, 10));
+
+        futures_lite::future::block_on(stream.try_next()).unwrap();
+
+        let delegate = stream.into_delegate().unwrap();
+        assert_eq!(delegate.offset, 4);
+        assert!(PaginatedStream::<NumbersDelegate>::Closed
+            .into_delegate()
+            .is_none());
+    }
+
+    #[test]
+    fn test_try_count() {
+        let stream = PaginatedStream::from(NumbersDelegate::new(3, 10));
+        assert_eq!(futures_lite::future::block_on(stream.try_count()), Ok(10));
+
+        let mut delegate = NumbersDelegate::new(3, 10);
+        delegate.fail_at = Some(6);
+        let stream = PaginatedStream::from(delegate);
+        assert_eq!(futures_lite::future::block_on(stream.try_count()), Err(6));
+    }
+
+    #[test]
+    fn test_try_collect_all() {
+        let stream = PaginatedStream::from(NumbersDelegate::new(3, 7));
+        assert_eq!(
+            futures_lite::future::block_on(stream.try_collect_all()),
+            Ok((0..7).collect())
+        );
+
+        let mut delegate = NumbersDelegate::new(3, 7);
+        delegate.fail_at = Some(0);
+        let mut stream = PaginatedStream::from(delegate);
+
+        futures_lite::future::block_on(async {
+            assert_eq!(
+                futures_lite::StreamExt::next(&mut stream).await,
+                Some(Err(0))
+            );
+            // A closed stream stays closed, no matter how many times it is
+            // polled, and the error is not reported again.
+            assert_eq!(futures_lite::StreamExt::next(&mut stream).await, None);
+            assert!(matches!(stream, PaginatedStream::Closed));
+            assert_eq!(stream.try_collect_all().await, Ok(Vec::new()));
+        });
+    }
+
+    #[test]
+    fn test_with_initial_page() {
+        use futures_core::Stream;
+
+        // Requesting the first page again would fail, and the total is only
+        // known from the page that was already received.
+        let mut delegate = NumbersDelegate::new(3, 100);
+        delegate.fail_at = Some(0);
+        let mut stream = PaginatedStream::with_initial_page(delegate, vec![0, 1, 2], Some(8));
+        assert_eq!(stream.size_hint(), (8, Some(8)));
+
+        futures_lite::future::block_on(async {
+            for expected in 0..3 {
+                assert_eq!(
+                    futures_lite::StreamExt::next(&mut stream).await,
+                    Some(Ok(expected))
+                );
+            }
+
+            // Every seeded item has been yielded, and still nothing has been
+            // requested; the second page is only requested by the next poll.
+            match &stream {
+                PaginatedStream::Ready(ReadyStateValue { delegate, items }) => {
+                    assert_eq!(delegate.offset, 3);
+                    assert!(items.is_empty());
+                }
+                _ => panic!("expected the seeded page to still be ready"),
+            }
+
+            assert_eq!(stream.try_collect_all().await, Ok((3..8).collect()));
+        });
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "did not advance past 0 after a page of 3 items")]
+    fn test_offset_must_advance() {
+        /// Forgets the offset that it is given, so it would always request the
+        /// first page again.
+        struct StuckDelegate;
+
+        #[async_trait]
+        impl PaginationDelegate for StuckDelegate {
+            type Item = usize;
+            type Error = ();
+
+            async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+                Ok(vec![0, 1, 2])
+            }
+
+            fn offset(&self) -> usize {
+                0
+            }
+
+            fn set_offset(&mut self, _value: usize) {}
+
+            fn total_items(&self) -> Option<usize> {
+                None
+            }
+        }
+
+        let mut stream = PaginatedStream::from(StuckDelegate);
+        futures_lite::future::block_on(futures_lite::StreamExt::next(&mut stream));
+    }
+
+    #[test]
+    fn test_collect_with() {
+        use std::cell::Cell;
+
+        let cancelled = Cell::new(false);
+        let mut reports = Vec::new();
+        let cancel = futures_lite::future::poll_fn(|_| match cancelled.get() {
+            true => std::task::Poll::Ready(()),
+            false => std::task::Poll::Pending,
+        });
+
+        let items = futures_lite::future::block_on(
+            PaginatedStream::from(NumbersDelegate::new(3, 12)).collect_with(
+                |count, total| {
+                    reports.push((count, total));
+                    // The user cancels while the second page is being
+                    // collected.
+                    if count == 5 {
+                        cancelled.set(true);
+                    }
+                },
+                cancel,
+            ),
+        );
+
+        // The rest of the second page was already received, but is not
+        // collected after the cancellation.
+        assert_eq!(items, Ok(vec![0, 1, 2, 3, 4]));
+        assert_eq!(
+            reports,
+            (1..=5).map(|count| (count, Some(12))).collect::<Vec<_>>()
+        );
+
+        let items = futures_lite::future::block_on(
+            PaginatedStream::from(NumbersDelegate::new(3, 7))
+                .collect_with(|_, _| (), futures_lite::future::pending::<()>()),
+        );
+        assert_eq!(items, Ok((0..7).collect()));
+    }
+
+    #[test]
+    fn test_size_hint() {
+        use futures_core::Stream;
+        use futures_lite::StreamExt;
+
+        let mut stream = PaginatedStream::from(NumbersDelegate::new(4, 10));
+
+        // The delegate knows the total before the first page is requested.
+        assert_eq!(stream.size_hint(), (10, Some(10)));
+
+        let first = futures_lite::future::block_on(stream.next());
+        assert_eq!(first, Some(Ok(0)));
+        // Three items are left on the page, and six have not been requested.
+        assert_eq!(stream.size_hint(), (9, Some(9)));
+
+        let rest = futures_lite::future::block_on((&mut stream).take(5).collect::<Vec<_>>());
+        assert_eq!(rest.len(), 5);
+        assert_eq!(stream.size_hint(), (4, Some(4)));
+    }
+
+    #[test]
+    fn test_try_collect_all_huge_total() {
+        // The first page claims an impossible total, which must not be
+        // allocated for.
+        let mut delegate = NumbersDelegate::new(3, usize::MAX);
+        delegate.shrink_to = Some(5);
+        let stream = PaginatedStream::from(delegate);
+
+        assert_eq!(
+            futures_lite::future::block_on(stream.try_collect_all()),
+            Ok((0..5).collect())
+        );
+    }
+
+    #[test]
+    fn test_shrinking_total() {
+        // The second page has items 3, 4, and 5, but says there are only 5.
+        let mut delegate = NumbersDelegate::new(3, 9);
+        delegate.shrink_to = Some(5);
+        let stream = PaginatedStream::from(delegate);
+        assert_eq!(
+            futures_lite::future::block_on(stream.try_collect_all()),
+            Ok((0..5).collect())
+        );
+
+        // The second page says there are fewer items than were already yielded.
+        let mut delegate = NumbersDelegate::new(3, 9);
+        delegate.shrink_to = Some(2);
+        let stream = PaginatedStream::from(delegate);
+        assert_eq!(
+            futures_lite::future::block_on(stream.try_collect_all()),
+            Ok((0..3).collect())
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use super::{PaginatedStream, PaginationDelegate, ReadyStateValue};
+
+/// Yields the items of a [`PaginatedStream`] a whole page at a time, rather
+/// than one by one. Construct this with [`PaginatedStream::pages`].
+pub struct Pages<'f, D>
+where
+    D: PaginationDelegate,
+{
+    stream: PaginatedStream<'f, D>,
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    /// Yield every page as a vector of its items, as soon as it has been
+    /// received, such as to insert each page into a database as one batch.
+    ///
+    /// The pages are requested by this same stream, so the offset of the
+    /// delegate is kept the same way, and a page is truncated to the total the
+    /// same way. If some items of the current page have already been yielded
+    /// from this stream, the first page contains only those that remain. An
+    /// error is yielded as usual, and ends the stream.
+    pub fn pages(self) -> Pages<'f, D> {
+        Pages { stream: self }
+    }
+}
+
+impl<'f, D> Pages<'f, D>
+where
+    D: PaginationDelegate,
+{
+    /// Consume this, taking out the stream that the pages are taken from.
+    pub fn into_inner(self) -> PaginatedStream<'f, D> {
+        self.stream
+    }
+
+    /// The items of the current page that the stream has not yielded yet.
+    fn buffered(&mut self) -> Option<&mut VecDeque<D::Item>> {
+        match &mut self.stream {
+            PaginatedStream::Ready(ReadyStateValue { items, .. }) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+impl<'f, D> Unpin for Pages<'f, D>
+where
+    D: PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+}
+
+impl<'f, D> Stream for Pages<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    type Item = Result<Vec<D::Item>, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        // Whatever is left of the current page comes first. Otherwise, the
+        // stream requests the next page, and yields its first item, after
+        // which the rest of it is ready.
+        let mut page = match this.buffered() {
+            Some(items) if !items.is_empty() => Vec::new(),
+            _ => match Pin::new(&mut this.stream).poll_next(ctx) {
+                Poll::Ready(Some(Ok(item))) => vec![item],
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            },
+        };
+
+        if let Some(items) = this.buffered() {
+            page.extend(items.drain(..));
+        }
+
+        Poll::Ready(Some(Ok(page)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.stream.size_hint();
+
+        // There are no more pages than there are items.
+        (lower.min(1), upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::StreamExt;
+
+    use crate::paginator::tests::NumbersDelegate;
+    use crate::paginator::PaginatedStream;
+
+    #[test]
+    fn test_pages() {
+        let stream = PaginatedStream::from(NumbersDelegate::new(3, 8)).pages();
+
+        let pages = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+        assert_eq!(
+            pages,
+            [Ok(vec![0, 1, 2]), Ok(vec![3, 4, 5]), Ok(vec![6, 7])]
+        );
+    }
+
+    #[test]
+    fn test_pages_after_items() {
+        let mut delegate = NumbersDelegate::new(4, 12);
+        delegate.fail_at = Some(8);
+        let mut stream = PaginatedStream::from(delegate);
+
+        let first = futures_lite::future::block_on(stream.next());
+        assert_eq!(first, Some(Ok(0)));
+
+        // Only the rest of the first page is left, and then the error closes
+        // the stream.
+        let pages = futures_lite::future::block_on(stream.pages().collect::<Vec<_>>());
+        assert_eq!(pages, [Ok(vec![1, 2, 3]), Ok(vec![4, 5, 6, 7]), Err(8)]);
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::{Future, Stream};
+
+use super::{advance_offset, PaginatedStream, PaginationDelegate};
+
+/// Determines when a [`PrefetchingStream`] requests pages, relative to how
+/// many items have been consumed from it.
+///
+/// Regardless of the policy, items are always yielded in the same order as the
+/// pages were requested (the order of their offsets), and each item is yielded
+/// exactly once. The only differences are when requests are made, and how many
+/// may be in flight at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrefetchPolicy {
+    /// Request the next page only after every item of the current page has
+    /// been yielded, and the stream is polled again. This is the behavior of
+    /// [`PaginatedStream`].
+    #[default]
+    Lazy,
+    /// Keep up to this many pages buffered ahead of the page that is currently
+    /// being yielded from. Only one request is in flight at a time, and each
+    /// one is made with the delegate after the previous page has arrived, so
+    /// pages are received in order. `ReadAhead(0)` is the same as `Lazy`.
+    ReadAhead(usize),
+    /// Keep up to `limit` requests in flight at the same time, each made with
+    /// its own clone of the delegate, at an offset computed from
+    /// [`PaginationDelegate::page_size`]. Pages may arrive in any order, but
+    /// are held back until every page before them has been yielded.
+    ///
+    /// The first page is always requested alone, so that the delegate can
+    /// learn the total number of items. If the total is known, no request is
+    /// made for an offset beyond it. If it is not, requests are made
+    /// speculatively, and when a page returns fewer items than the page size,
+    /// every request after it is discarded. If the delegate does not report a
+    /// page size, this behaves like `ReadAhead(limit - 1)`.
+    Concurrent {
+        /// The maximum number of requests that may be in flight at once.
+        limit: usize,
+    },
+}
+
+type PageFuture<'f, D> = dyn Future<
+        Output = (
+            D,
+            Result<Vec<<D as PaginationDelegate>::Item>, <D as PaginationDelegate>::Error>,
+        ),
+    > + 'f;
+
+/// A request that has been made for a page, and the offset that it was made
+/// at. Once the future resolves, the output is kept until every request before
+/// it has been handled.
+enum PageRequest<'f, D: PaginationDelegate> {
+    Pending(Pin<Box<PageFuture<'f, D>>>),
+    Done(D, Result<Vec<D::Item>, D::Error>),
+}
+
+/// A paginated stream that can request pages before they are needed, as
+/// determined by its [`PrefetchPolicy`]. Construct this with
+/// [`PaginatedStream::with_prefetch`].
+///
+/// Like [`PaginatedStream`], this yields every item of every page, and if a
+/// request resolves to an error, that error is yielded once and the stream is
+/// closed. Any other requests in flight at that moment are dropped.
+pub struct PrefetchingStream<'f, D: PaginationDelegate> {
+    policy: PrefetchPolicy,
+    /// The delegate, while it is not owned by a sequential request. For
+    /// concurrent requests, this is cloned for each one, and replaced by the
+    /// clone of the most recent page to be handled.
+    delegate: Option<D>,
+    /// Requests that have been made, in the order of their offsets.
+    requests: VecDeque<PageRequest<'f, D>>,
+    /// Pages that have been received but not yet fully yielded.
+    pages: VecDeque<VecDeque<D::Item>>,
+    /// The offset that the next concurrent request will be made at.
+    next_offset: usize,
+    /// Whether at least one page has been received.
+    started: bool,
+    /// Whether the last page has been received, or an error was yielded.
+    exhausted: bool,
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Clone,
+{
+    /// Construct a stream that requests pages from the `delegate` according
+    /// to the `policy`. See [`PrefetchPolicy`] for the ordering guarantees
+    /// and request pattern of each.
+    pub fn with_prefetch(delegate: D, policy: PrefetchPolicy) -> PrefetchingStream<'f, D> {
+        PrefetchingStream {
+            policy,
+            next_offset: delegate.offset(),
+            delegate: Some(delegate),
+            requests: VecDeque::new(),
+            pages: VecDeque::new(),
+            started: false,
+            exhausted: false,
+        }
+    }
+
+    /// Construct a stream that keeps up to `n` requests for pages in flight at
+    /// the same time, yielding their items in order. This is shorthand for
+    /// [`PaginatedStream::with_prefetch`] with [`PrefetchPolicy::Concurrent`],
+    /// see that for the details. The delegate must report its
+    /// [`PaginationDelegate::page_size`] for requests to be made concurrently.
+    pub fn buffered(delegate: D, n: usize) -> PrefetchingStream<'f, D> {
+        Self::with_prefetch(delegate, PrefetchPolicy::Concurrent { limit: n })
+    }
+}
+
+impl<'f, D> PrefetchingStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Clone,
+{
+    /// The policy that this stream was constructed with.
+    pub fn policy(&self) -> PrefetchPolicy {
+        self.policy
+    }
+
+    /// Start as many requests as the policy allows at this moment.
+    fn fill(&mut self) {
+        // Pages that have been emptied don't count towards the read-ahead.
+        while matches!(self.pages.front(), Some(page) if page.is_empty()) {
+            self.pages.pop_front();
+        }
+
+        if self.exhausted {
+            return;
+        }
+
+        let page_size = self.delegate.as_ref().and_then(D::page_size);
+
+        match (self.policy, page_size) {
+            (PrefetchPolicy::Concurrent { limit }, Some(page_size)) if self.started => {
+                let delegate = self.delegate.as_ref().unwrap();
+                let total = delegate.total_items().unwrap_or(usize::MAX);
+
+                while self.requests.len() < limit.max(1) && self.next_offset < total {
+                    let mut fork = delegate.clone();
+
+                    fork.set_offset(self.next_offset);
+                    self.next_offset += page_size;
+                    self.requests.push_back(Self::request(fork));
+                }
+            }
+            (policy, _) => {
+                let ahead = match policy {
+                    PrefetchPolicy::Lazy => 0,
+                    PrefetchPolicy::ReadAhead(ahead) => ahead,
+                    PrefetchPolicy::Concurrent { limit } => limit.saturating_sub(1),
+                };
+
+                if self.requests.is_empty() && self.pages.len() <= ahead {
+                    if let Some(delegate) = self.delegate.take() {
+                        self.requests.push_back(Self::request(delegate));
+                    }
+                }
+            }
+        }
+    }
+
+    fn request(mut delegate: D) -> PageRequest<'f, D> {
+        PageRequest::Pending(Box::pin(async move {
+            let result = delegate.next_page().await;
+            (delegate, result)
+        }))
+    }
+}
+
+// None of the fields are ever pinned, the futures of requests are boxed.
+impl<'f, D: PaginationDelegate> Unpin for PrefetchingStream<'f, D> {}
+
+impl<'f, D> Stream for PrefetchingStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Clone,
+{
+    type Item = Result<D::Item, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            this.fill();
+
+            // Poll every request that is still pending, so that any of them
+            // can make progress, even if they are not at the front.
+            for request in this.requests.iter_mut() {
+                if let PageRequest::Pending(future) = request {
+                    if let Poll::Ready((delegate, result)) = future.as_mut().poll(ctx) {
+                        *request = PageRequest::Done(delegate, result);
+                    }
+                }
+            }
+
+            let mut received = false;
+
+            // Handle the responses in order, stopping at the first request that
+            // is still pending.
+            while let Some(PageRequest::Done(..)) = this.requests.front() {
+                let (mut delegate, result) = match this.requests.pop_front() {
+                    Some(PageRequest::Done(delegate, result)) => (delegate, result),
+                    _ => unreachable!(),
+                };
+
+                let mut items = match result {
+                    Ok(items) => items,
+                    Err(error) => {
+                        this.exhausted = true;
+                        this.requests.clear();
+                        this.pages.clear();
+                        return Poll::Ready(Some(Err(error)));
+                    }
+                };
+
+                let short = matches!(delegate.page_size(), Some(size) if items.len() < size);
+
+                // Never yield more than the latest total, the same as `PaginatedStream`.
+                if let Some(total) = delegate.total_items() {
+                    items.truncate(total.saturating_sub(delegate.offset()));
+                }
+
+                advance_offset(&mut delegate, items.len());
+                this.started = true;
+                received = true;
+
+                if items.is_empty()
+                    || short
+                    || delegate.offset() >= delegate.total_items().unwrap_or(usize::MAX)
+                {
+                    // This was the last page, so anything requested after it
+                    // would either be empty or a duplicate.
+                    this.exhausted = true;
+                    this.requests.clear();
+                }
+
+                this.next_offset = this.next_offset.max(delegate.offset());
+                this.delegate = Some(delegate);
+                this.pages.push_back(items.into());
+            }
+
+            if received {
+                // Give the policy a chance to make requests for the pages
+                // after these, before yielding any of their items.
+                continue;
+            }
+
+            if let Some(item) = this.pages.iter_mut().find_map(VecDeque::pop_front) {
+                return Poll::Ready(Some(Ok(item)));
+            }
+
+            if this.requests.is_empty() {
+                this.exhausted = true;
+                return Poll::Ready(None);
+            }
+
+            return Poll::Pending;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pages.iter().map(VecDeque::len).sum(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use futures_lite::StreamExt;
+
+    use super::PrefetchPolicy;
+    use crate::paginator::{PaginatedStream, PaginationDelegate};
+
+    #[derive(Debug, Default)]
+    struct Log {
+        /// The number of items that had been consumed when each request was
+        /// made, paired with the offset of that request.
+        requests: Vec<(usize, usize)>,
+        consumed: usize,
+        in_flight: usize,
+        max_in_flight: usize,
+    }
+
+    /// Pages over the numbers `0..total`, `size` at a time. The request for
+    /// each page yields to the executor a number of times, more for earlier
+    /// pages, so that concurrent requests finish out of order.
+    #[derive(Clone)]
+    struct InstrumentedDelegate {
+        log: Arc<Mutex<Log>>,
+        offset: usize,
+        size: usize,
+        total: usize,
+        /// Whether the total is reported by `total_items`.
+        known: bool,
+    }
+
+    #[async_trait]
+    impl PaginationDelegate for InstrumentedDelegate {
+        type Item = usize;
+        type Error = ();
+
+        async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+            {
+                let mut log = self.log.lock().unwrap();
+                let consumed = log.consumed;
+
+                log.requests.push((self.offset, consumed));
+                log.in_flight += 1;
+                log.max_in_flight = log.max_in_flight.max(log.in_flight);
+            }
+
+            for _ in 0..self.total.saturating_sub(self.offset) {
+                futures_lite::future::yield_now().await;
+            }
+
+            self.log.lock().unwrap().in_flight -= 1;
+
+            Ok((self.offset..self.total.min(self.offset + self.size)).collect())
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn set_offset(&mut self, value: usize) {
+            self.offset = value;
+        }
+
+        fn total_items(&self) -> Option<usize> {
+            self.known.then_some(self.total)
+        }
+
+        fn page_size(&self) -> Option<usize> {
+            Some(self.size)
+        }
+    }
+
+    /// Collect every item from a stream with the given policy, returning the
+    /// items and the log of requests.
+    fn collect(policy: PrefetchPolicy) -> (Vec<usize>, Log) {
+        collect_from(9, true, policy)
+    }
+
+    fn collect_from(total: usize, known: bool, policy: PrefetchPolicy) -> (Vec<usize>, Log) {
+        let log = Arc::new(Mutex::new(Log::default()));
+        let delegate = InstrumentedDelegate {
+            log: log.clone(),
+            offset: 0,
+            size: 3,
+            total,
+            known,
+        };
+        let mut stream = PaginatedStream::with_prefetch(delegate, policy);
+        let mut items = Vec::new();
+
+        futures_lite::future::block_on(async {
+            while let Some(item) = stream.next().await {
+                items.push(item.unwrap());
+                log.lock().unwrap().consumed += 1;
+            }
+        });
+
+        drop(stream);
+        let log = Arc::try_unwrap(log).unwrap().into_inner().unwrap();
+
+        (items, log)
+    }
+
+    #[test]
+    fn test_lazy() {
+        let (items, log) = collect(PrefetchPolicy::Lazy);
+
+        assert_eq!(items, (0..9).collect::<Vec<_>>());
+        // Each page is requested only once the previous one has been consumed.
+        assert_eq!(log.requests, [(0, 0), (3, 3), (6, 6)]);
+        assert_eq!(log.max_in_flight, 1);
+    }
+
+    #[test]
+    fn test_read_ahead() {
+        let (items, log) = collect(PrefetchPolicy::ReadAhead(1));
+
+        assert_eq!(items, (0..9).collect::<Vec<_>>());
+        // The second page is requested before anything is consumed, and each
+        // after it is requested as soon as one page is left in the buffer.
+        assert_eq!(log.requests, [(0, 0), (3, 0), (6, 3)]);
+        assert_eq!(log.max_in_flight, 1);
+    }
+
+    #[test]
+    fn test_concurrent() {
+        let (items, log) = collect(PrefetchPolicy::Concurrent { limit: 3 });
+
+        // The last page finishes first, but the order must be preserved.
+        assert_eq!(items, (0..9).collect::<Vec<_>>());
+        // The first page is requested alone, and then the rest all at once.
+        assert_eq!(log.requests, [(0, 0), (3, 0), (6, 0)]);
+        assert_eq!(log.max_in_flight, 2);
+    }
+
+    #[test]
+    fn test_buffered_short_last_page() {
+        // The total is not reported, so pages are requested speculatively. The
+        // page at 9 has only one item, so any pages after it that were already
+        // requested must be discarded.
+        let (items, log) = collect_from(10, false, PrefetchPolicy::Concurrent { limit: 4 });
+        let offsets = log
+            .requests
+            .iter()
+            .map(|(offset, _)| *offset)
+            .collect::<Vec<_>>();
+
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+        assert!(offsets.starts_with(&[0, 3, 6, 9]));
+        assert!(offsets[4..].iter().all(|offset| *offset >= 12));
+        assert!(offsets.len() > 4);
+    }
+}
@@ -0,0 +1,361 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use futures_core::{Future, Stream};
+use futures_timer::Delay;
+
+use super::{PaginatedStream, PaginationDelegate, ReadyStateValue};
+use crate::retry::RetryPolicy;
+
+/// Wraps another [`PaginationDelegate`], calling
+/// [`PaginationDelegate::next_page`] again whenever it fails with an error
+/// that is considered retryable, such as a `429 Too Many Requests` or a
+/// `503 Service Unavailable`, according to a [`RetryPolicy`].
+///
+/// Use [`PaginatedStream::retry_pages`] rather than constructing this
+/// directly.
+pub struct RetryDelegate<D, F> {
+    inner: D,
+    policy: RetryPolicy,
+    retryable: F,
+}
+
+impl<D, F> RetryDelegate<D, F> {
+    /// Wrap the `inner` delegate, retrying any page that fails with an error
+    /// for which `retryable` returns `true`, as many times as `policy` allows.
+    pub fn new(inner: D, policy: RetryPolicy, retryable: F) -> Self {
+        Self {
+            inner,
+            policy,
+            retryable,
+        }
+    }
+
+    /// Reference to the inner delegate.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Consume this delegate, taking out the inner delegate.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<D, F> PaginationDelegate for RetryDelegate<D, F>
+where
+    D: PaginationDelegate + Send,
+    D::Item: Send,
+    D::Error: Send,
+    F: Fn(&D::Error) -> bool + Send,
+{
+    type Item = D::Item;
+    type Error = D::Error;
+
+    async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+        let mut attempt = 0;
+
+        loop {
+            // The offset is only changed by `PaginatedStream` after a page
+            // has been received, so every attempt requests the same page.
+            match self.inner.next_page().await {
+                Err(error) if attempt < self.policy.attempts() && (self.retryable)(&error) => {
+                    let delay = self.policy.delay_for(attempt);
+
+                    if !delay.is_zero() {
+                        Delay::new(delay).await;
+                    }
+
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.inner.offset()
+    }
+
+    fn set_offset(&mut self, value: usize) {
+        self.inner.set_offset(value)
+    }
+
+    fn reset_offset(&mut self) {
+        self.inner.reset_offset()
+    }
+
+    fn total_items(&self) -> Option<usize> {
+        self.inner.total_items()
+    }
+
+    fn set_total_items(&mut self, value: usize) {
+        self.inner.set_total_items(value)
+    }
+
+    fn page_size(&self) -> Option<usize> {
+        self.inner.page_size()
+    }
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: PaginationDelegate + Send,
+    D::Item: Send,
+    D::Error: Send,
+{
+    /// Retry the request for any page that fails with an error for which
+    /// `retryable` returns `true`, rather than closing the stream. Requests
+    /// are retried as many times as the `policy` allows, waiting between them
+    /// as it specifies, and the same page is requested every time. If the
+    /// last attempt fails too, or the error is not retryable, that error is
+    /// yielded and the stream is closed as usual.
+    ///
+    /// # Panics
+    ///
+    /// This must not be called while a page is `Pending`, because the
+    /// delegate is owned by the future of that request.
+    pub fn retry_pages<F>(
+        self,
+        policy: RetryPolicy,
+        retryable: F,
+    ) -> PaginatedStream<'f, RetryDelegate<D, F>>
+    where
+        F: Fn(&D::Error) -> bool + Send,
+    {
+        use PaginatedStream::*;
+
+        match self {
+            Request(delegate) => Request(RetryDelegate::new(delegate, policy, retryable)),
+            Ready(ReadyStateValue { delegate, items }) => Ready(ReadyStateValue {
+                delegate: RetryDelegate::new(delegate, policy, retryable),
+                items,
+            }),
+            Closed => Closed,
+            Pending(_) | Indeterminate => {
+                panic!("cannot retry pages while a page request is pending")
+            }
+        }
+    }
+}
+
+type RetryFuture<'f, T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + 'f>>;
+
+/// Retries the items of a [`PaginatedStream`] that are themselves errors,
+/// rather than yielding them immediately. Construct this with
+/// [`PaginatedStream::retry_items`].
+pub struct RetryItems<'f, D, F, T, E>
+where
+    D: PaginationDelegate<Item = Result<T, E>>,
+{
+    stream: PaginatedStream<'f, D>,
+    policy: RetryPolicy,
+    refetch: F,
+    /// The number of the attempt in flight, and the future of it.
+    retry: Option<(usize, RetryFuture<'f, T, E>)>,
+}
+
+impl<'f, D, T, E> PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate<Item = Result<T, E>> + Unpin,
+    T: 'f + Unpin,
+    E: 'f + Unpin,
+{
+    /// For a delegate that yields each item as a [`Result`] (such as when each
+    /// item of a page is parsed individually), retry any item that is an `Err`
+    /// by calling `refetch` with the error, and awaiting the future that it
+    /// returns. This is repeated until that future resolves to `Ok`, or the
+    /// `policy` runs out of attempts, at which point the last error is yielded
+    /// in place of the item. The error must contain whatever is needed to
+    /// refetch the item, such as its ID.
+    ///
+    /// Items that are `Ok` are yielded as they are, and the order of all items
+    /// is preserved. Errors from the delegate itself (failing to get a page)
+    /// are not retried, and close the stream as usual.
+    pub fn retry_items<F, Fut>(self, policy: RetryPolicy, refetch: F) -> RetryItems<'f, D, F, T, E>
+    where
+        F: FnMut(&E) -> Fut,
+        Fut: 'f + Future<Output = Result<T, E>>,
+    {
+        RetryItems {
+            stream: self,
+            policy,
+            refetch,
+            retry: None,
+        }
+    }
+}
+
+impl<'f, D, F, Fut, T, E> RetryItems<'f, D, F, T, E>
+where
+    D: PaginationDelegate<Item = Result<T, E>>,
+    F: FnMut(&E) -> Fut,
+    Fut: 'f + Future<Output = Result<T, E>>,
+{
+    /// Start the retry numbered `attempt` for the item that failed with
+    /// `error`, or give the error back if there are no attempts left.
+    fn start_retry(&mut self, attempt: usize, error: E) -> Result<(), E> {
+        if attempt >= self.policy.attempts() {
+            return Err(error);
+        }
+
+        let delay = self.policy.delay_for(attempt);
+        let refetch = (self.refetch)(&error);
+
+        self.retry = Some((
+            attempt,
+            Box::pin(async move {
+                if !delay.is_zero() {
+                    Delay::new(delay).await;
+                }
+
+                refetch.await
+            }),
+        ));
+
+        Ok(())
+    }
+}
+
+// The closure is never pinned, and the future of the retry is boxed.
+impl<'f, D, F, T, E> Unpin for RetryItems<'f, D, F, T, E>
+where
+    D: PaginationDelegate<Item = Result<T, E>> + Unpin,
+    T: Unpin,
+    E: Unpin,
+{
+}
+
+impl<'f, D, F, Fut, T, E> Stream for RetryItems<'f, D, F, T, E>
+where
+    D: 'f + PaginationDelegate<Item = Result<T, E>> + Unpin,
+    F: FnMut(&E) -> Fut,
+    Fut: 'f + Future<Output = Result<T, E>>,
+    T: 'f + Unpin,
+    E: 'f + Unpin,
+{
+    type Item = Result<Result<T, E>, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some((attempt, future)) = &mut this.retry {
+                let attempt = *attempt;
+
+                match future.as_mut().poll(ctx) {
+                    Poll::Ready(Ok(item)) => {
+                        this.retry = None;
+                        return Poll::Ready(Some(Ok(Ok(item))));
+                    }
+                    Poll::Ready(Err(error)) => {
+                        this.retry = None;
+
+                        if let Err(error) = this.start_retry(attempt + 1, error) {
+                            return Poll::Ready(Some(Ok(Err(error))));
+                        }
+
+                        continue;
+                    }
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+
+            return match Pin::new(&mut this.stream).poll_next(ctx) {
+                Poll::Ready(Some(Ok(Err(error)))) => match this.start_retry(0, error) {
+                    Ok(()) => continue,
+                    Err(error) => Poll::Ready(Some(Ok(Err(error)))),
+                },
+                poll => poll,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use futures_lite::StreamExt;
+
+    use crate::paginator::tests::NumbersDelegate;
+    use crate::paginator::{PaginatedStream, PaginationDelegate};
+    use crate::retry::RetryPolicy;
+
+    /// Serves a single page, where the item with ID `2` failed, and the item
+    /// with ID `4` failed permanently. The error is the ID of the item.
+    struct FlakyDelegate {
+        offset: usize,
+    }
+
+    #[async_trait]
+    impl PaginationDelegate for FlakyDelegate {
+        type Item = Result<u32, u32>;
+        type Error = ();
+
+        async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+            Ok(vec![Ok(1), Err(2), Ok(3), Err(4)])
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn set_offset(&mut self, value: usize) {
+            self.offset = value;
+        }
+
+        fn total_items(&self) -> Option<usize> {
+            Some(4)
+        }
+    }
+
+    #[test]
+    fn test_retry_items() {
+        let calls = Cell::new(0);
+        let stream = PaginatedStream::from(FlakyDelegate { offset: 0 }).retry_items(
+            RetryPolicy::new(2).with_delay(Duration::from_millis(1)),
+            |id: &u32| {
+                let id = *id;
+                calls.set(calls.get() + 1);
+                // Item 2 succeeds on the second attempt, item 4 never does.
+                let result = match (id, calls.get()) {
+                    (2, 2) => Ok(id),
+                    _ => Err(id),
+                };
+                async move { result }
+            },
+        );
+
+        let items = futures_lite::future::block_on(stream.map(Result::unwrap).collect::<Vec<_>>());
+
+        assert_eq!(items, [Ok(1), Ok(2), Ok(3), Err(4)]);
+        // Two attempts for item 2, and both attempts of the policy for item 4.
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn test_retry_pages() {
+        let policy = RetryPolicy::new(2).with_delay(Duration::from_millis(1));
+        let collect = |fail_times, retryable: fn(&usize) -> bool| {
+            let mut delegate = NumbersDelegate::new(3, 9);
+            delegate.fail_at = Some(3);
+            delegate.fail_times = Some(fail_times);
+            let stream = PaginatedStream::from(delegate).retry_pages(policy, retryable);
+
+            futures_lite::future::block_on(stream.collect::<Vec<_>>())
+        };
+
+        // Two failures are covered by two retries, and the same page is
+        // requested again, so nothing is skipped or repeated.
+        assert_eq!(collect(2, |_| true), (0..9).map(Ok).collect::<Vec<_>>());
+        // A third failure is one too many.
+        assert_eq!(collect(3, |_| true), [Ok(0), Ok(1), Ok(2), Err(3)]);
+        // An error that is not retryable closes the stream immediately.
+        assert_eq!(collect(1, |_| false), [Ok(0), Ok(1), Ok(2), Err(3)]);
+    }
+}
@@ -0,0 +1,304 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::{Future, Stream};
+
+use super::{PaginatedStream, PaginationDelegate};
+
+type TeardownFuture<'f> = Pin<Box<dyn Future<Output = ()> + Send + 'f>>;
+type Teardown<'f, T> = Box<dyn FnOnce(T) -> TeardownFuture<'f> + 'f>;
+type Spawn<'f> = Box<dyn FnOnce(TeardownFuture<'f>) + 'f>;
+
+/// Paginates over a snapshot that is created on the server before the first
+/// page is requested, and must be released after the last, such as the
+/// point-in-time ID for Elasticsearch's `search_after`.
+///
+/// The snapshot is acquired by [`SnapshotPaginator::new`], which also builds
+/// the delegate with it, so that it can be sent with every page. When the
+/// stream ends, or a page fails, the snapshot is released before the last
+/// poll resolves, so that it is released even if the caller stops at the
+/// error. If this is dropped before then, such as by a `take` or a timeout,
+/// the future of the teardown is handed to the `spawn` function that was
+/// given to [`SnapshotPaginator::new`], because [`Drop`] can't await it, and
+/// blocking on it would stall the executor that it is dropped on. To wait for
+/// the snapshot to be released instead, use [`SnapshotPaginator::close`].
+pub struct SnapshotPaginator<'f, D, T>
+where
+    D: PaginationDelegate,
+{
+    stream: PaginatedStream<'f, D>,
+    snapshot: Option<T>,
+    teardown: Option<Teardown<'f, T>>,
+    spawn: Option<Spawn<'f>>,
+    /// The future of the teardown that is in progress, and the error to yield
+    /// once it is done, if a page failed.
+    closing: Option<(TeardownFuture<'f>, Option<D::Error>)>,
+}
+
+impl<'f, D, T> SnapshotPaginator<'f, D, T>
+where
+    D: PaginationDelegate,
+    T: Clone,
+{
+    /// Acquire a snapshot by awaiting the future from `setup`, and build the
+    /// delegate that requests the pages with `build`, which is given a clone
+    /// of the snapshot. When pagination is done, the snapshot is given to
+    /// `teardown`, the future of which is awaited to release it.
+    ///
+    /// If this is dropped while the snapshot is still held, the future from
+    /// `teardown` is given to `spawn` instead, which is expected to run it to
+    /// completion in the background, such as with `tokio::spawn`.
+    ///
+    /// If `setup` fails, its error is returned, and there is nothing to
+    /// release.
+    pub async fn new<S, SFut, B, E, F, Fut, P>(
+        setup: S,
+        build: B,
+        teardown: F,
+        spawn: P,
+    ) -> Result<Self, E>
+    where
+        S: FnOnce() -> SFut,
+        SFut: Future<Output = Result<T, E>>,
+        B: FnOnce(T) -> D,
+        F: 'f + FnOnce(T) -> Fut,
+        Fut: 'f + Future<Output = ()> + Send,
+        P: 'f + FnOnce(Pin<Box<dyn Future<Output = ()> + Send + 'f>>),
+    {
+        let snapshot = setup().await?;
+
+        Ok(Self {
+            stream: PaginatedStream::from(build(snapshot.clone())),
+            snapshot: Some(snapshot),
+            teardown: Some(Box::new(move |snapshot| Box::pin(teardown(snapshot)))),
+            spawn: Some(Box::new(spawn)),
+            closing: None,
+        })
+    }
+}
+
+impl<'f, D, T> SnapshotPaginator<'f, D, T>
+where
+    D: PaginationDelegate,
+{
+    /// The snapshot that the pages are requested from, or `None` if it has
+    /// been released.
+    pub fn snapshot(&self) -> Option<&T> {
+        self.snapshot.as_ref()
+    }
+
+    /// Release the snapshot now, without requesting any more pages. If it has
+    /// already been released, this does nothing.
+    ///
+    /// Unlike dropping the stream, which leaves the teardown to the function
+    /// that spawns it, this resolves once the snapshot has been released.
+    pub async fn close(mut self) {
+        if let Some(closing) = self.take_teardown() {
+            closing.await;
+        }
+    }
+
+    /// Take out the future that releases the snapshot, whether it is in
+    /// progress or not, if it has not been released yet.
+    fn take_teardown(&mut self) -> Option<TeardownFuture<'f>> {
+        if let Some((closing, _)) = self.closing.take() {
+            return Some(closing);
+        }
+
+        match (self.snapshot.take(), self.teardown.take()) {
+            (Some(snapshot), Some(teardown)) => Some(teardown(snapshot)),
+            _ => None,
+        }
+    }
+}
+
+// Neither the snapshot, the teardown, nor the error are ever pinned, and the
+// future of the teardown is boxed.
+impl<'f, D, T> Unpin for SnapshotPaginator<'f, D, T>
+where
+    D: PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+}
+
+impl<'f, D, T> Stream for SnapshotPaginator<'f, D, T>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    type Item = Result<D::Item, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some((closing, _)) = &mut this.closing {
+                if closing.as_mut().poll(ctx).is_pending() {
+                    return Poll::Pending;
+                }
+
+                // Use of unwrap:
+                // This was just matched as `Some` above.
+                let (_, error) = this.closing.take().unwrap();
+                return Poll::Ready(error.map(Err));
+            }
+
+            return match Pin::new(&mut this.stream).poll_next(ctx) {
+                Poll::Ready(Some(Ok(item))) => Poll::Ready(Some(Ok(item))),
+                // The snapshot is released before the error is yielded, or the
+                // end is reported, because the stream won't yield anything else
+                // that would give it another chance.
+                Poll::Ready(result) => match this.take_teardown() {
+                    Some(closing) => {
+                        this.closing = Some((closing, result.and_then(Result::err)));
+                        continue;
+                    }
+                    None => Poll::Ready(result),
+                },
+                Poll::Pending => Poll::Pending,
+            };
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.closing {
+            Some((_, error)) => (error.is_some() as usize, Some(error.is_some() as usize)),
+            None => self.stream.size_hint(),
+        }
+    }
+}
+
+impl<'f, D, T> Drop for SnapshotPaginator<'f, D, T>
+where
+    D: PaginationDelegate,
+{
+    fn drop(&mut self) {
+        // The teardown can't be awaited from here, and blocking on it could
+        // deadlock the executor, so it is left to run in the background.
+        if let (Some(closing), Some(spawn)) = (self.take_teardown(), self.spawn.take()) {
+            spawn(closing);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+
+    use futures_lite::StreamExt;
+
+    use super::{SnapshotPaginator, TeardownFuture};
+    use crate::paginator::tests::NumbersDelegate;
+
+    type Released = Arc<Mutex<Vec<&'static str>>>;
+    type Spawned = Rc<RefCell<Vec<TeardownFuture<'static>>>>;
+
+    /// Paginate with a snapshot that is recorded in `released` when it is
+    /// released. A teardown that is spawned is pushed to `spawned`, to be run
+    /// by the test.
+    fn paginate(
+        delegate: NumbersDelegate,
+        released: &Released,
+        spawned: &Spawned,
+    ) -> SnapshotPaginator<'static, NumbersDelegate, &'static str> {
+        let (released, spawned) = (released.clone(), spawned.clone());
+        let stream = SnapshotPaginator::new(
+            || async { Ok::<_, ()>("pit-1") },
+            |snapshot| {
+                assert_eq!(snapshot, "pit-1");
+                delegate
+            },
+            move |snapshot| async move { released.lock().unwrap().push(snapshot) },
+            move |closing| spawned.borrow_mut().push(closing),
+        );
+
+        futures_lite::future::block_on(stream).unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_released() {
+        let released = Released::default();
+        let spawned = Spawned::default();
+        let mut stream = paginate(NumbersDelegate::new(3, 7), &released, &spawned);
+
+        futures_lite::future::block_on(async {
+            for expect in 0..7 {
+                assert_eq!(stream.next().await, Some(Ok(expect)));
+            }
+            assert!(released.lock().unwrap().is_empty());
+
+            // Released before the end is reported, and only once.
+            assert_eq!(stream.next().await, None);
+            assert_eq!(*released.lock().unwrap(), ["pit-1"]);
+            assert_eq!(stream.snapshot(), None);
+            assert_eq!(stream.next().await, None);
+        });
+
+        drop(stream);
+        assert_eq!(released.lock().unwrap().len(), 1);
+        assert!(spawned.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_released_on_error() {
+        let released = Released::default();
+        let spawned = Spawned::default();
+        let mut delegate = NumbersDelegate::new(3, 7);
+        delegate.fail_at = Some(3);
+        let mut stream = paginate(delegate, &released, &spawned);
+
+        let items = futures_lite::future::block_on(async {
+            let mut items = Vec::new();
+            while let Some(item) = stream.next().await {
+                let failed = item.is_err();
+                items.push(item);
+
+                // Released before the error is yielded.
+                if failed {
+                    assert_eq!(*released.lock().unwrap(), ["pit-1"]);
+                }
+            }
+            items
+        });
+
+        assert_eq!(items, [Ok(0), Ok(1), Ok(2), Err(3)]);
+        assert_eq!(released.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_released_on_close() {
+        let released = Released::default();
+        let spawned = Spawned::default();
+        let mut stream = paginate(NumbersDelegate::new(3, 7), &released, &spawned);
+
+        futures_lite::future::block_on(async {
+            assert_eq!(stream.next().await, Some(Ok(0)));
+            stream.close().await;
+        });
+        assert_eq!(*released.lock().unwrap(), ["pit-1"]);
+        assert!(spawned.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_released_on_drop() {
+        let released = Released::default();
+        let spawned = Spawned::default();
+        let stream = paginate(NumbersDelegate::new(3, 7), &released, &spawned);
+
+        // Stopping early, the same as a `take` that is satisfied.
+        let items = futures_lite::future::block_on(stream.take(2).collect::<Vec<_>>());
+        assert_eq!(items, [Ok(0), Ok(1)]);
+
+        // The teardown was handed off rather than blocked on, and releases the
+        // snapshot once it is run.
+        assert!(released.lock().unwrap().is_empty());
+        let closing = spawned.borrow_mut().drain(..).collect::<Vec<_>>();
+        assert_eq!(closing.len(), 1);
+        for closing in closing {
+            futures_lite::future::block_on(closing);
+        }
+        assert_eq!(*released.lock().unwrap(), ["pit-1"]);
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use futures_core::Stream;
+
+use super::{PaginatedStream, PaginationDelegate};
+
+/// The half of a split [`PaginatedStream`] that yields the items. Construct
+/// this with [`PaginatedStream::split_results`].
+pub struct SplitItems<'f, D>
+where
+    D: PaginationDelegate,
+{
+    shared: Arc<Mutex<Shared<'f, D>>>,
+}
+
+/// The half of a split [`PaginatedStream`] that yields the errors. Construct
+/// this with [`PaginatedStream::split_results`].
+pub struct SplitErrors<'f, D>
+where
+    D: PaginationDelegate,
+{
+    shared: Arc<Mutex<Shared<'f, D>>>,
+}
+
+/// The stream being split, and everything that it has yielded for the half
+/// that has not polled for it yet.
+struct Shared<'f, D>
+where
+    D: PaginationDelegate,
+{
+    stream: PaginatedStream<'f, D>,
+    done: bool,
+    items: Half<D::Item>,
+    errors: Half<D::Error>,
+}
+
+struct Half<T> {
+    queue: VecDeque<T>,
+    waker: Option<Waker>,
+    dropped: bool,
+}
+
+impl<T> Default for Half<T> {
+    fn default() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            waker: None,
+            dropped: false,
+        }
+    }
+}
+
+impl<T> Half<T> {
+    /// Queue a value that was received by the other half, and wake whatever
+    /// is waiting for it. If this half has been dropped, the value is too.
+    fn push(&mut self, value: T) {
+        if !self.dropped {
+            self.queue.push_back(value);
+            self.wake();
+        }
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    /// Split this stream into two: one that yields the items, and one that
+    /// yields the errors, so that each can be sent somewhere different. Both
+    /// halves poll the same stream, and whatever one of them receives that
+    /// belongs to the other is held until the other is polled for it.
+    ///
+    /// Both halves should be polled concurrently, such as by joining them, or
+    /// one fully before the other; the items are buffered without limit while
+    /// only the errors are being polled. If either half is dropped, whatever
+    /// would have been yielded by it is dropped too, so the other half can be
+    /// used alone to ignore the items or errors.
+    ///
+    /// Keep in mind that an error from [`PaginationDelegate::next_page`] is
+    /// fatal to a [`PaginatedStream`], because no page after it can be
+    /// requested. In that case, the error is yielded once by [`SplitErrors`],
+    /// and both halves end after everything before it has been yielded. To
+    /// continue past errors that can be recovered from, use
+    /// [`PaginatedStream::retry_pages`] before splitting, so that only the
+    /// errors that could not be recovered from are routed here.
+    pub fn split_results(self) -> (SplitItems<'f, D>, SplitErrors<'f, D>) {
+        let shared = Arc::new(Mutex::new(Shared {
+            stream: self,
+            done: false,
+            items: Half::default(),
+            errors: Half::default(),
+        }));
+
+        (
+            SplitItems {
+                shared: shared.clone(),
+            },
+            SplitErrors { shared },
+        )
+    }
+}
+
+impl<'f, D> Shared<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    /// Poll the stream being split once, queueing whatever it yields for the
+    /// half that it belongs to. Resolves to `true` if something was queued,
+    /// or `false` if the stream has ended.
+    fn poll_source(&mut self, ctx: &mut Context<'_>) -> Poll<bool> {
+        if self.done {
+            return Poll::Ready(false);
+        }
+
+        match Pin::new(&mut self.stream).poll_next(ctx) {
+            Poll::Ready(Some(Ok(item))) => self.items.push(item),
+            Poll::Ready(Some(Err(error))) => self.errors.push(error),
+            Poll::Ready(None) => {
+                self.done = true;
+                self.items.wake();
+                self.errors.wake();
+                return Poll::Ready(false);
+            }
+            Poll::Pending => return Poll::Pending,
+        }
+
+        Poll::Ready(true)
+    }
+}
+
+impl<'f, D> Stream for SplitItems<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    type Item = D::Item;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Use of unwrap:
+        // The lock is never held across a poll of anything other than the
+        // stream being split, which would have to panic to poison it.
+        let mut shared = self.shared.lock().unwrap();
+
+        loop {
+            if let Some(item) = shared.items.queue.pop_front() {
+                return Poll::Ready(Some(item));
+            }
+
+            // The stream being split will only wake the half that polled it
+            // last, so the other half has to be woken when something is
+            // queued for it.
+            shared.items.waker = Some(ctx.waker().clone());
+
+            match shared.poll_source(ctx) {
+                Poll::Ready(true) => continue,
+                Poll::Ready(false) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<'f, D> Stream for SplitErrors<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    type Item = D::Error;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Use of unwrap:
+        // See the implementation for `SplitItems`.
+        let mut shared = self.shared.lock().unwrap();
+
+        loop {
+            if let Some(error) = shared.errors.queue.pop_front() {
+                return Poll::Ready(Some(error));
+            }
+
+            shared.errors.waker = Some(ctx.waker().clone());
+
+            match shared.poll_source(ctx) {
+                Poll::Ready(true) => continue,
+                Poll::Ready(false) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<'f, D> Drop for SplitItems<'f, D>
+where
+    D: PaginationDelegate,
+{
+    fn drop(&mut self) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.items.dropped = true;
+            shared.items.queue.clear();
+        }
+    }
+}
+
+impl<'f, D> Drop for SplitErrors<'f, D>
+where
+    D: PaginationDelegate,
+{
+    fn drop(&mut self) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.errors.dropped = true;
+            shared.errors.queue.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::StreamExt;
+
+    use crate::paginator::tests::NumbersDelegate;
+    use crate::paginator::PaginatedStream;
+
+    #[test]
+    fn test_split_results() {
+        let mut delegate = NumbersDelegate::new(4, 12);
+        delegate.fail_at = Some(8);
+        let (items, errors) = PaginatedStream::from(delegate).split_results();
+
+        let (items, errors) = futures_lite::future::block_on(futures_lite::future::zip(
+            items.collect::<Vec<_>>(),
+            errors.collect::<Vec<_>>(),
+        ));
+
+        // The page at 8 failed, which closed the stream, so the items before it
+        // went one way, and the error the other.
+        assert_eq!(items, (0..8).collect::<Vec<_>>());
+        assert_eq!(errors, [8]);
+    }
+
+    #[test]
+    fn test_split_results_dropped_half() {
+        let (items, errors) = PaginatedStream::from(NumbersDelegate::new(4, 10)).split_results();
+        drop(items);
+
+        // The items are dropped as they are received, rather than buffered.
+        let errors = futures_lite::future::block_on(errors.collect::<Vec<_>>());
+        assert!(errors.is_empty());
+    }
+}
@@ -0,0 +1,272 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use futures_core::{Future, Stream};
+
+/// The items of a single page, as returned by
+/// [`StreamingPaginationDelegate::next_page`]. Each item may fail to parse
+/// independently, which closes the [`StreamingPaginatedStream`] that is
+/// consuming it.
+pub type PageStream<T, E> = Pin<Box<dyn Stream<Item = Result<T, E>> + Send>>;
+
+/// A variant of [`PaginationDelegate`] for APIs with pages large enough that
+/// buffering and parsing an entire page before yielding the first item of it
+/// is undesirable. Rather than a vector, the request for each page resolves to
+/// a [`PageStream`], which is expected to parse items out of the response body
+/// as it is received.
+///
+/// After implementing this on a type, use `StreamingPaginatedStream::from` to
+/// get an iterable stream from the delegate.
+///
+/// [`PaginationDelegate`]: super::PaginationDelegate
+#[async_trait]
+pub trait StreamingPaginationDelegate {
+    /// This is the type of the item that calls to `poll_next` are expected to
+    /// yield.
+    type Item;
+    /// This is the type of error that can occur both when requesting a page,
+    /// and when parsing any one of its items.
+    type Error;
+
+    /// Performs an asynchronous request for the next page, resolving once the
+    /// response has begun to be received, with a stream of the items that the
+    /// page contains. Only the request itself should be awaited here, not the
+    /// entire body.
+    async fn next_page(&mut self) -> Result<PageStream<Self::Item, Self::Error>, Self::Error>;
+
+    /// Gets the current offset, which will be the index at the end of the
+    /// current/previous page. The value returned from this will be changed by
+    /// [`StreamingPaginatedStream`] immediately after the stream of a page
+    /// ends, increasing by the number of items that it yielded.
+    fn offset(&self) -> usize;
+
+    /// Sets the offset for the next page. The offset is required to be the
+    /// index of the last item from the previous page.
+    fn set_offset(&mut self, value: usize);
+
+    /// Gets the total count of items that are currently expected from the API.
+    /// See [`PaginationDelegate::total_items`].
+    ///
+    /// [`PaginationDelegate::total_items`]: super::PaginationDelegate::total_items
+    fn total_items(&self) -> Option<usize>;
+}
+
+type PendingPageFuture<'f, D> = dyn Future<
+        Output = (
+            D,
+            Result<
+                PageStream<
+                    <D as StreamingPaginationDelegate>::Item,
+                    <D as StreamingPaginationDelegate>::Error,
+                >,
+                <D as StreamingPaginationDelegate>::Error,
+            >,
+        ),
+    > + 'f;
+
+enum State<'f, D: StreamingPaginationDelegate> {
+    Request(D),
+    Pending(Pin<Box<PendingPageFuture<'f, D>>>),
+    Streaming {
+        delegate: D,
+        page: PageStream<D::Item, D::Error>,
+        count: usize,
+    },
+    Closed,
+}
+
+/// Yields the items of every page from a [`StreamingPaginationDelegate`], as
+/// soon as each one has been parsed. Like [`PaginatedStream`], the next page is
+/// only requested after every item of the current page has been yielded, and
+/// the stream is polled again. The stream closes after the first error, or
+/// after a page that is empty or reaches the total number of items.
+///
+/// [`PaginatedStream`]: super::PaginatedStream
+pub struct StreamingPaginatedStream<'f, D: StreamingPaginationDelegate> {
+    state: State<'f, D>,
+}
+
+impl<'f, D> From<D> for StreamingPaginatedStream<'f, D>
+where
+    D: StreamingPaginationDelegate,
+{
+    fn from(other: D) -> Self {
+        Self {
+            state: State::Request(other),
+        }
+    }
+}
+
+impl<'f, D> StreamingPaginatedStream<'f, D>
+where
+    D: StreamingPaginationDelegate,
+{
+    /// Returns `true` if the stream has closed, and will not yield any more
+    /// items.
+    pub fn is_closed(&self) -> bool {
+        matches!(self.state, State::Closed)
+    }
+}
+
+// None of the fields are ever pinned, the futures and streams are boxed.
+impl<'f, D: StreamingPaginationDelegate> Unpin for StreamingPaginatedStream<'f, D> {}
+
+impl<'f, D> Stream for StreamingPaginatedStream<'f, D>
+where
+    D: 'f + StreamingPaginationDelegate,
+{
+    type Item = Result<D::Item, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            match std::mem::replace(&mut this.state, State::Closed) {
+                State::Request(mut delegate) => {
+                    this.state = State::Pending(Box::pin(async move {
+                        let result = delegate.next_page().await;
+                        (delegate, result)
+                    }));
+                }
+                State::Pending(mut future) => match future.as_mut().poll(ctx) {
+                    Poll::Ready((delegate, Ok(page))) => {
+                        this.state = State::Streaming {
+                            delegate,
+                            page,
+                            count: 0,
+                        };
+                    }
+                    // The state is already `Closed`.
+                    Poll::Ready((_, Err(error))) => return Poll::Ready(Some(Err(error))),
+                    Poll::Pending => {
+                        this.state = State::Pending(future);
+                        return Poll::Pending;
+                    }
+                },
+                State::Streaming {
+                    mut delegate,
+                    mut page,
+                    count,
+                } => match page.as_mut().poll_next(ctx) {
+                    Poll::Ready(Some(Ok(item))) => {
+                        this.state = State::Streaming {
+                            delegate,
+                            page,
+                            count: count + 1,
+                        };
+                        return Poll::Ready(Some(Ok(item)));
+                    }
+                    Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                    Poll::Ready(None) => {
+                        // The page has ended, so the offset can be updated now
+                        // that the number of items it had is known.
+                        delegate.set_offset(delegate.offset() + count);
+
+                        if count == 0
+                            || delegate.offset() >= delegate.total_items().unwrap_or(usize::MAX)
+                        {
+                            return Poll::Ready(None);
+                        }
+
+                        this.state = State::Request(delegate);
+                    }
+                    Poll::Pending => {
+                        this.state = State::Streaming {
+                            delegate,
+                            page,
+                            count,
+                        };
+                        return Poll::Pending;
+                    }
+                },
+                State::Closed => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use futures_lite::StreamExt;
+
+    use super::{PageStream, StreamingPaginatedStream, StreamingPaginationDelegate};
+
+    /// Serves pages of JSON Lines, parsing each line only when the stream of
+    /// the page is polled for the next item.
+    struct LinesDelegate {
+        pages: Vec<&'static str>,
+        offset: usize,
+        parsed: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl StreamingPaginationDelegate for LinesDelegate {
+        type Item = u32;
+        type Error = String;
+
+        async fn next_page(&mut self) -> Result<PageStream<Self::Item, Self::Error>, Self::Error> {
+            let body = self.pages.remove(0);
+            let parsed = self.parsed.clone();
+
+            Ok(Box::pin(futures_lite::stream::iter(body.lines()).map(
+                move |line| {
+                    parsed.fetch_add(1, Ordering::SeqCst);
+                    serde_json::from_str(line).map_err(|error| error.to_string())
+                },
+            )))
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn set_offset(&mut self, value: usize) {
+            self.offset = value;
+        }
+
+        fn total_items(&self) -> Option<usize> {
+            Some(5)
+        }
+    }
+
+    #[test]
+    fn test_streaming_items() {
+        let parsed = Arc::new(AtomicUsize::new(0));
+        let mut stream = StreamingPaginatedStream::from(LinesDelegate {
+            pages: vec!["1\n2\n3", "4\n5"],
+            offset: 0,
+            parsed: parsed.clone(),
+        });
+
+        futures_lite::future::block_on(async {
+            // Only the first item has been parsed when it is yielded.
+            assert_eq!(stream.next().await, Some(Ok(1)));
+            assert_eq!(parsed.load(Ordering::SeqCst), 1);
+
+            let rest = (&mut stream).collect::<Vec<_>>().await;
+            assert_eq!(rest, [Ok(2), Ok(3), Ok(4), Ok(5)]);
+            assert!(stream.is_closed());
+        });
+    }
+
+    #[test]
+    fn test_streaming_item_error() {
+        let mut stream = StreamingPaginatedStream::from(LinesDelegate {
+            pages: vec!["1\nnope\n3"],
+            offset: 0,
+            parsed: Arc::default(),
+        });
+
+        futures_lite::future::block_on(async {
+            assert_eq!(stream.next().await, Some(Ok(1)));
+            assert!(matches!(stream.next().await, Some(Err(_))));
+            // An item that fails to parse closes the stream.
+            assert_eq!(stream.next().await, None);
+        });
+    }
+}
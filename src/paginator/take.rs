@@ -0,0 +1,132 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use super::{PaginatedStream, PaginationDelegate};
+
+/// Yields no more than a number of items from a [`PaginatedStream`].
+/// Construct this with [`PaginatedStream::take_items`].
+pub struct TakeItems<'f, D>
+where
+    D: PaginationDelegate,
+{
+    stream: PaginatedStream<'f, D>,
+    remaining: usize,
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    /// Yield no more than the first `n` items, such as to look at the first
+    /// hundred results, no matter how many pages there are.
+    ///
+    /// After the `n`th item is yielded, this ends, and the stream is not
+    /// polled again, even if there are more items of the same page buffered,
+    /// so no page after the one containing that item is ever requested. Errors
+    /// are yielded as usual, and do not count towards `n`.
+    ///
+    /// The offset of the delegate is kept correct throughout, so the stream can
+    /// be taken back out with [`TakeItems::into_inner`] to continue from the
+    /// item after the last one that was yielded.
+    pub fn take_items(self, n: usize) -> TakeItems<'f, D> {
+        TakeItems {
+            stream: self,
+            remaining: n,
+        }
+    }
+}
+
+impl<'f, D> TakeItems<'f, D>
+where
+    D: PaginationDelegate,
+{
+    /// The number of items that may still be yielded.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Consume this, taking out the stream that the items are taken from. Any
+    /// items of the current page that have not been yielded are still in it.
+    pub fn into_inner(self) -> PaginatedStream<'f, D> {
+        self.stream
+    }
+}
+
+impl<'f, D> Unpin for TakeItems<'f, D>
+where
+    D: PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+}
+
+impl<'f, D> Stream for TakeItems<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+    type Item = Result<D::Item, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.remaining == 0 {
+            return Poll::Ready(None);
+        }
+
+        let poll = Pin::new(&mut this.stream).poll_next(ctx);
+        if let Poll::Ready(Some(Ok(_))) = poll {
+            this.remaining -= 1;
+        }
+
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.stream.size_hint();
+
+        (
+            lower.min(self.remaining),
+            Some(upper.map_or(self.remaining, |upper| upper.min(self.remaining))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_core::Stream;
+    use futures_lite::StreamExt;
+
+    use crate::paginator::tests::NumbersDelegate;
+    use crate::paginator::{PaginatedStream, ReadyStateValue};
+
+    #[test]
+    fn test_take_items() {
+        let mut stream = PaginatedStream::from(NumbersDelegate::new(4, 100)).take_items(6);
+        assert_eq!(stream.size_hint().1, Some(6));
+
+        let items = futures_lite::future::block_on((&mut stream).collect::<Vec<_>>());
+        assert_eq!(items, (0..6).map(Ok).collect::<Vec<_>>());
+        assert_eq!(stream.size_hint(), (0, Some(0)));
+
+        // The sixth item is in the middle of the second page, so that is the
+        // last page that was requested, and the rest of it is left.
+        match stream.into_inner() {
+            PaginatedStream::Ready(ReadyStateValue { delegate, items }) => {
+                assert_eq!(delegate.offset, 8);
+                assert_eq!(items, [6, 7]);
+            }
+            _ => panic!("expected the last page to still be ready"),
+        }
+    }
+
+    #[test]
+    fn test_take_items_exhausted() {
+        let stream = PaginatedStream::from(NumbersDelegate::new(4, 5)).take_items(10);
+
+        let items = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+        assert_eq!(items, (0..5).map(Ok).collect::<Vec<_>>());
+    }
+}
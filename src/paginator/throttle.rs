@@ -0,0 +1,330 @@
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use futures_timer::Delay;
+
+use super::{PaginatedStream, PaginationDelegate, ReadyStateValue};
+use crate::endpoints::RateLimit;
+
+/// A [`PaginationDelegate`] that reports the rate limit of the API it pages
+/// over, for use with [`ThrottleDelegate`].
+///
+/// This is usually implemented by keeping the result of
+/// [`ApiResponse::rate_limit`] from the response of the last page.
+///
+/// [`ApiResponse::rate_limit`]: crate::endpoints::ApiResponse::rate_limit
+pub trait RateLimitedPaginationDelegate: PaginationDelegate {
+    /// Gets the rate limit that was reported with the last page that was
+    /// received, or `None` if there hasn't been one, or the response to it
+    /// didn't report one.
+    fn rate_limit(&self) -> Option<RateLimit>;
+}
+
+/// Wraps another [`RateLimitedPaginationDelegate`], holding back the request
+/// for the next page while the API reports that there are no requests left in
+/// the current window, until the window resets. Unlike a fixed delay between
+/// pages, this only waits when the server would otherwise reject the request,
+/// and then only for exactly as long as necessary.
+///
+/// The timer is a function that returns a future resolving after the given
+/// duration, which is [`futures_timer::Delay::new`] unless another is given
+/// with [`ThrottleDelegate::with_timer`].
+///
+/// Use [`PaginatedStream::throttle_by_rate_limit`] or
+/// [`PaginatedStream::throttle_by_rate_limit_with`] rather than constructing
+/// this directly.
+pub struct ThrottleDelegate<D, T = fn(Duration) -> Delay> {
+    inner: D,
+    timer: T,
+}
+
+impl<D> ThrottleDelegate<D> {
+    /// Wrap the `inner` delegate, waiting out its rate limit before every
+    /// page.
+    pub fn new(inner: D) -> Self {
+        Self::with_timer(inner, Delay::new)
+    }
+}
+
+impl<D, T> ThrottleDelegate<D, T> {
+    /// Wrap the `inner` delegate, waiting out its rate limit before every
+    /// page with the future that `timer` returns for the time until the reset.
+    pub fn with_timer(inner: D, timer: T) -> Self {
+        Self { inner, timer }
+    }
+
+    /// Reference to the inner delegate.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Consume this delegate, taking out the inner delegate.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<D, T> ThrottleDelegate<D, T>
+where
+    D: RateLimitedPaginationDelegate,
+{
+    /// The time to wait before the next page can be requested, which is zero
+    /// unless the budget of the current window has been used up, and it has
+    /// not reset yet.
+    fn wait(&self, now: SystemTime) -> Duration {
+        match self.inner.rate_limit() {
+            Some(limit) if limit.remaining == 0 => {
+                limit.reset.duration_since(now).unwrap_or_default()
+            }
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+#[async_trait]
+impl<D, T, F> PaginationDelegate for ThrottleDelegate<D, T>
+where
+    D: RateLimitedPaginationDelegate + Send,
+    D::Item: Send,
+    T: Fn(Duration) -> F + Send,
+    F: Future<Output = ()> + Send,
+{
+    type Item = D::Item;
+    type Error = D::Error;
+
+    async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+        let wait = self.wait(SystemTime::now());
+
+        if !wait.is_zero() {
+            (self.timer)(wait).await;
+        }
+
+        self.inner.next_page().await
+    }
+
+    fn offset(&self) -> usize {
+        self.inner.offset()
+    }
+
+    fn set_offset(&mut self, value: usize) {
+        self.inner.set_offset(value)
+    }
+
+    fn reset_offset(&mut self) {
+        self.inner.reset_offset()
+    }
+
+    fn total_items(&self) -> Option<usize> {
+        self.inner.total_items()
+    }
+
+    fn set_total_items(&mut self, value: usize) {
+        self.inner.set_total_items(value)
+    }
+
+    fn page_size(&self) -> Option<usize> {
+        self.inner.page_size()
+    }
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: RateLimitedPaginationDelegate + Send,
+    D::Item: Send,
+{
+    /// Pause before requesting a page whenever the delegate reports that the
+    /// rate limit of the API has no requests remaining, until the time that
+    /// it resets. The delegate must implement
+    /// [`RateLimitedPaginationDelegate`].
+    ///
+    /// The time is measured with [`futures_timer::Delay`]. To use the timer
+    /// of an executor instead, see [`Self::throttle_by_rate_limit_with`].
+    ///
+    /// # Panics
+    ///
+    /// This must not be called while a page is `Pending`, because the
+    /// delegate is owned by the future of that request.
+    pub fn throttle_by_rate_limit(self) -> PaginatedStream<'f, ThrottleDelegate<D>> {
+        self.map_throttle_delegate(ThrottleDelegate::new)
+    }
+
+    /// Same as [`Self::throttle_by_rate_limit`], but pausing with the future
+    /// that `timer` returns when it is called with the time until the reset.
+    ///
+    /// # Panics
+    ///
+    /// This must not be called while a page is `Pending`, because the
+    /// delegate is owned by the future of that request.
+    pub fn throttle_by_rate_limit_with<T, F>(
+        self,
+        timer: T,
+    ) -> PaginatedStream<'f, ThrottleDelegate<D, T>>
+    where
+        T: Fn(Duration) -> F + Send,
+        F: Future<Output = ()> + Send,
+    {
+        self.map_throttle_delegate(|delegate| ThrottleDelegate::with_timer(delegate, timer))
+    }
+
+    fn map_throttle_delegate<T>(
+        self,
+        wrap: impl FnOnce(D) -> ThrottleDelegate<D, T>,
+    ) -> PaginatedStream<'f, ThrottleDelegate<D, T>>
+    where
+        ThrottleDelegate<D, T>: PaginationDelegate<Item = D::Item>,
+    {
+        use PaginatedStream::*;
+
+        match self {
+            Request(delegate) => Request(wrap(delegate)),
+            Ready(ReadyStateValue { delegate, items }) => Ready(ReadyStateValue {
+                delegate: wrap(delegate),
+                items,
+            }),
+            Closed => Closed,
+            Pending(_) | Indeterminate => {
+                panic!("cannot throttle by the rate limit while a page request is pending")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+
+    use async_trait::async_trait;
+
+    use super::RateLimitedPaginationDelegate;
+    use crate::endpoints::RateLimit;
+    use crate::paginator::{PaginatedStream, PaginationDelegate};
+
+    /// Pages over the numbers `0..total`, two at a time, with a budget of
+    /// `limit` requests per window. The window resets `window` after the first
+    /// request in it, as reported with every page. The time of every request
+    /// is read from `clock`, which only moves when a test moves it.
+    struct LimitedDelegate {
+        offset: usize,
+        total: usize,
+        limit: u64,
+        window: Duration,
+        clock: Arc<Mutex<SystemTime>>,
+        rate_limit: Option<RateLimit>,
+    }
+
+    impl LimitedDelegate {
+        fn new(total: usize, limit: u64, window: Duration, clock: Arc<Mutex<SystemTime>>) -> Self {
+            Self {
+                offset: 0,
+                total,
+                limit,
+                window,
+                clock,
+                rate_limit: None,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl PaginationDelegate for LimitedDelegate {
+        type Item = usize;
+        type Error = ();
+
+        async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+            let now = *self.clock.lock().unwrap();
+            let rate_limit = match self.rate_limit {
+                // Requesting before the reset with no budget left would be
+                // rejected by a real server.
+                Some(limit) if limit.reset > now && limit.remaining == 0 => return Err(()),
+                Some(limit) if limit.reset > now => RateLimit {
+                    remaining: limit.remaining - 1,
+                    ..limit
+                },
+                _ => RateLimit {
+                    limit: self.limit,
+                    remaining: self.limit - 1,
+                    reset: now + self.window,
+                },
+            };
+            self.rate_limit = Some(rate_limit);
+
+            Ok((self.offset..self.total.min(self.offset + 2)).collect())
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn set_offset(&mut self, value: usize) {
+            self.offset = value;
+        }
+
+        fn total_items(&self) -> Option<usize> {
+            Some(self.total)
+        }
+    }
+
+    impl RateLimitedPaginationDelegate for LimitedDelegate {
+        fn rate_limit(&self) -> Option<RateLimit> {
+            self.rate_limit
+        }
+    }
+
+    #[test]
+    fn test_throttle_by_rate_limit() {
+        let window = Duration::from_secs(60);
+        let clock = Arc::new(Mutex::new(SystemTime::now()));
+        let waits = Arc::new(Mutex::new(Vec::new()));
+        // Resolves right away, but moves the clock of the delegate to when the
+        // wait would have ended, which is no earlier than the reset.
+        let timer = {
+            let clock = clock.clone();
+            let waits = waits.clone();
+            move |wait| {
+                *clock.lock().unwrap() = SystemTime::now() + wait;
+                waits.lock().unwrap().push(wait);
+                futures_lite::future::ready(())
+            }
+        };
+        let stream = PaginatedStream::from(LimitedDelegate::new(8, 2, window, clock))
+            .throttle_by_rate_limit_with(timer);
+
+        // Four pages with two requests per window means waiting for the reset
+        // once, after the second page, for about as long as was left until it.
+        let items = futures_lite::future::block_on(stream.try_collect_all());
+        assert_eq!(items, Ok((0..8).collect()));
+
+        let waits = waits.lock().unwrap();
+        assert_eq!(waits.len(), 1);
+        assert!(waits[0] <= window && waits[0] > window - Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_throttle_by_rate_limit_with() {
+        let window = Duration::from_secs(60);
+        let clock = Arc::new(Mutex::new(SystemTime::now()));
+        let waits = Arc::new(Mutex::new(Vec::new()));
+        let timer = {
+            let waits = waits.clone();
+            move |wait| {
+                waits.lock().unwrap().push(wait);
+                futures_lite::future::ready(())
+            }
+        };
+        let stream = PaginatedStream::from(LimitedDelegate::new(8, 2, window, clock))
+            .throttle_by_rate_limit_with(timer);
+
+        // The timer resolves right away without moving the clock, so the
+        // request after the pause is made before the window resets, and fails.
+        let items = futures_lite::future::block_on(stream.try_collect_all());
+        assert_eq!(items, Err(()));
+
+        // Only the request after the budget was used up waited, for about as
+        // long as was left until the reset.
+        let waits = waits.lock().unwrap();
+        assert_eq!(waits.len(), 1);
+        assert!(waits[0] <= window && waits[0] > window - Duration::from_secs(1));
+    }
+}
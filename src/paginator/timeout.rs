@@ -0,0 +1,309 @@
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_timer::Delay;
+
+use super::{PaginatedStream, PaginationDelegate, ReadyStateValue};
+
+/// Error type of a [`TimeoutDelegate`]. Either the inner delegate failed to
+/// get the next page, or it did not finish getting it in time.
+#[derive(Debug)]
+pub enum TimeoutError<E> {
+    /// The inner delegate resolved to an error before the timeout elapsed.
+    Inner(E),
+    /// The request for a single page took longer than the contained
+    /// duration, and was abandoned.
+    PageTimeout(Duration),
+}
+
+impl<E> fmt::Display for TimeoutError<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inner(error) => error.fmt(f),
+            Self::PageTimeout(duration) => {
+                write!(
+                    f,
+                    "request for the next page timed out after {:?}",
+                    duration
+                )
+            }
+        }
+    }
+}
+
+impl<E> std::error::Error for TimeoutError<E>
+where
+    E: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Inner(error) => Some(error),
+            Self::PageTimeout(_) => None,
+        }
+    }
+}
+
+/// Wraps another [`PaginationDelegate`], racing every call to
+/// [`PaginationDelegate::next_page`] against a timer. This bounds how long any
+/// one page may take, independently of how long the entire stream takes.
+///
+/// The timer is a function that returns a future resolving after the given
+/// duration, which is [`futures_timer::Delay::new`] unless another is given
+/// with [`TimeoutDelegate::with_timer`], such as the `sleep` of the runtime
+/// that is already in use.
+///
+/// Use [`PaginatedStream::page_timeout`] or
+/// [`PaginatedStream::page_timeout_with`] rather than constructing this
+/// directly.
+pub struct TimeoutDelegate<D, T = fn(Duration) -> Delay> {
+    inner: D,
+    duration: Duration,
+    timer: T,
+}
+
+impl<D> TimeoutDelegate<D> {
+    /// Wrap the `inner` delegate, timing out each page after `duration`.
+    pub fn new(inner: D, duration: Duration) -> Self {
+        Self::with_timer(inner, duration, Delay::new)
+    }
+}
+
+impl<D, T> TimeoutDelegate<D, T> {
+    /// Wrap the `inner` delegate, timing out each page after `duration`, as
+    /// measured by the future that `timer` returns for it.
+    pub fn with_timer(inner: D, duration: Duration, timer: T) -> Self {
+        Self {
+            inner,
+            duration,
+            timer,
+        }
+    }
+
+    /// Reference to the inner delegate.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Consume this delegate, taking out the inner delegate.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<D, T, F> PaginationDelegate for TimeoutDelegate<D, T>
+where
+    D: PaginationDelegate + Send,
+    D::Item: Send,
+    D::Error: Send,
+    T: Fn(Duration) -> F + Send,
+    F: Future<Output = ()> + Send,
+{
+    type Item = D::Item;
+    type Error = TimeoutError<D::Error>;
+
+    async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+        let duration = self.duration;
+        let request = async { self.inner.next_page().await.map_err(TimeoutError::Inner) };
+        let timer = (self.timer)(duration);
+        let timeout = async {
+            timer.await;
+            Err(TimeoutError::PageTimeout(duration))
+        };
+
+        // Whichever finishes first wins, and the other is dropped. If the timer
+        // wins, the offset is never changed, because `PaginatedStream` only
+        // updates it after a page has been received.
+        futures_lite::future::or(request, timeout).await
+    }
+
+    fn offset(&self) -> usize {
+        self.inner.offset()
+    }
+
+    fn set_offset(&mut self, value: usize) {
+        self.inner.set_offset(value)
+    }
+
+    fn reset_offset(&mut self) {
+        self.inner.reset_offset()
+    }
+
+    fn total_items(&self) -> Option<usize> {
+        self.inner.total_items()
+    }
+
+    fn set_total_items(&mut self, value: usize) {
+        self.inner.set_total_items(value)
+    }
+
+    fn page_size(&self) -> Option<usize> {
+        self.inner.page_size()
+    }
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: PaginationDelegate + Send,
+    D::Item: Send,
+    D::Error: Send,
+{
+    /// Bound the time that each individual page request may take. If the
+    /// request for a page is still pending after `duration`, the stream
+    /// yields [`TimeoutError::PageTimeout`] and closes, just as it would for
+    /// any other error. Errors from the delegate itself are wrapped in
+    /// [`TimeoutError::Inner`].
+    ///
+    /// The time is measured with [`futures_timer::Delay`], which runs on a
+    /// thread of its own, so this works with any executor. To use the timer
+    /// of an executor instead, see [`Self::page_timeout_with`].
+    ///
+    /// # Panics
+    ///
+    /// This must not be called while a page is `Pending`, because the
+    /// delegate is owned by the future of that request.
+    pub fn page_timeout(self, duration: Duration) -> PaginatedStream<'f, TimeoutDelegate<D>> {
+        self.map_timeout_delegate(|delegate| TimeoutDelegate::new(delegate, duration))
+    }
+
+    /// Same as [`Self::page_timeout`], but measuring the time with the future
+    /// that `timer` returns when it is called with `duration`, which is raced
+    /// against the request for every page. This is for using the timer of the
+    /// executor that is already running, such as `tokio::time::sleep`, or
+    /// `async_io::Timer::after` with its output discarded.
+    ///
+    /// # Panics
+    ///
+    /// This must not be called while a page is `Pending`, because the
+    /// delegate is owned by the future of that request.
+    pub fn page_timeout_with<T, F>(
+        self,
+        duration: Duration,
+        timer: T,
+    ) -> PaginatedStream<'f, TimeoutDelegate<D, T>>
+    where
+        T: Fn(Duration) -> F + Send,
+        F: Future<Output = ()> + Send,
+    {
+        self.map_timeout_delegate(|delegate| TimeoutDelegate::with_timer(delegate, duration, timer))
+    }
+
+    fn map_timeout_delegate<T>(
+        self,
+        wrap: impl FnOnce(D) -> TimeoutDelegate<D, T>,
+    ) -> PaginatedStream<'f, TimeoutDelegate<D, T>>
+    where
+        TimeoutDelegate<D, T>: PaginationDelegate<Item = D::Item>,
+    {
+        use PaginatedStream::*;
+
+        match self {
+            Request(delegate) => Request(wrap(delegate)),
+            Ready(ReadyStateValue { delegate, items }) => Ready(ReadyStateValue {
+                delegate: wrap(delegate),
+                items,
+            }),
+            Closed => Closed,
+            Pending(_) | Indeterminate => {
+                panic!("cannot set a page timeout while a page request is pending")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use futures_lite::StreamExt;
+
+    use super::TimeoutError;
+    use crate::paginator::{PaginatedStream, PaginationDelegate};
+
+    /// Yields two items for the first page, and hangs forever on the second.
+    struct HangingDelegate {
+        offset: usize,
+    }
+
+    #[async_trait]
+    impl PaginationDelegate for HangingDelegate {
+        type Item = usize;
+        type Error = ();
+
+        async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+            match self.offset {
+                0 => Ok(vec![0, 1]),
+                _ => futures_lite::future::pending().await,
+            }
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn set_offset(&mut self, value: usize) {
+            self.offset = value;
+        }
+
+        fn total_items(&self) -> Option<usize> {
+            Some(4)
+        }
+    }
+
+    #[test]
+    fn test_page_timeout() {
+        let duration = Duration::from_millis(50);
+        let mut stream =
+            PaginatedStream::from(HangingDelegate { offset: 0 }).page_timeout(duration);
+
+        futures_lite::future::block_on(async {
+            // The first page arrives in time.
+            assert_eq!(stream.next().await.unwrap().unwrap(), 0);
+            assert_eq!(stream.next().await.unwrap().unwrap(), 1);
+
+            // The second page never arrives, so it must time out.
+            match stream.next().await {
+                Some(Err(TimeoutError::PageTimeout(elapsed))) => assert_eq!(elapsed, duration),
+                _ => panic!("expected the second page to time out"),
+            }
+
+            // The error is only reported once.
+            assert!(matches!(stream, PaginatedStream::Closed));
+            assert!(stream.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn test_page_timeout_with() {
+        let duration = Duration::from_secs(60);
+        let timers = Arc::new(AtomicUsize::new(0));
+        let timer = {
+            let timers = timers.clone();
+            move |elapsed| {
+                assert_eq!(elapsed, duration);
+                timers.fetch_add(1, Ordering::SeqCst);
+                // A timer that has already elapsed, so that the test doesn't
+                // have to wait for it, and only a page that is ready right away
+                // is received.
+                futures_lite::future::ready(())
+            }
+        };
+        let stream =
+            PaginatedStream::from(HangingDelegate { offset: 0 }).page_timeout_with(duration, timer);
+
+        let items = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+        assert!(matches!(
+            items.as_slice(),
+            [Ok(0), Ok(1), Err(TimeoutError::PageTimeout(_))]
+        ));
+        assert_eq!(timers.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,124 @@
+use std::future::Future;
+
+use async_trait::async_trait;
+
+use super::{PaginatedStream, PaginationDelegate};
+
+/// A [`PaginationDelegate`] that requests each page by calling a function with
+/// the state that the previous page left behind. Construct a stream with this
+/// using [`PaginatedStream::unfold`].
+pub struct UnfoldDelegate<S, F> {
+    state: Option<S>,
+    unfold: F,
+    offset: usize,
+}
+
+#[async_trait]
+impl<S, F, Fut, T, E> PaginationDelegate for UnfoldDelegate<S, F>
+where
+    S: Send,
+    F: FnMut(S) -> Fut + Send,
+    Fut: Future<Output = Result<Option<(Vec<T>, S)>, E>> + Send,
+    T: Send,
+{
+    type Item = T;
+    type Error = E;
+
+    async fn next_page(&mut self) -> Result<Vec<Self::Item>, Self::Error> {
+        // The state is only missing after the function has returned `None`,
+        // which would have closed the stream with the empty page that it was
+        // turned into.
+        let state = match self.state.take() {
+            Some(state) => state,
+            None => return Ok(Vec::new()),
+        };
+
+        match (self.unfold)(state).await? {
+            Some((items, state)) => {
+                self.state = Some(state);
+                Ok(items)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn set_offset(&mut self, value: usize) {
+        self.offset = value;
+    }
+
+    fn total_items(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl<'f, S, F, Fut, T, E> PaginatedStream<'f, UnfoldDelegate<S, F>>
+where
+    S: Send,
+    F: FnMut(S) -> Fut + Send,
+    Fut: Future<Output = Result<Option<(Vec<T>, S)>, E>> + Send,
+    T: Send,
+{
+    /// Create a stream without defining a delegate, by calling `unfold` for
+    /// every page, starting with the `seed` as its state. The function
+    /// resolves to the items of the page and the state to request the next
+    /// one with, or to `None` when there are no more pages. This is like
+    /// `futures::stream::unfold`, except that every page is flattened into its
+    /// items, and an error closes the stream, as with any other delegate.
+    ///
+    /// Because the delegate cannot know the total number of items, the stream
+    /// also ends after a page that is empty, even if `unfold` returned a state
+    /// for the next one.
+    ///
+    /// If the state can be reconstructed from the offset of the next page, it
+    /// is simpler to keep it in a [`PaginationDelegate`], which also enables
+    /// methods that depend on the offset, such as [`Self::reset`].
+    pub fn unfold(seed: S, unfold: F) -> Self {
+        PaginatedStream::from(UnfoldDelegate {
+            state: Some(seed),
+            unfold,
+            offset: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::StreamExt;
+
+    use crate::paginator::PaginatedStream;
+
+    #[test]
+    fn test_unfold() {
+        // Pages of three numbers at a time, counting up to ten.
+        let stream = PaginatedStream::unfold(0_u32, |start| async move {
+            if start >= 10 {
+                return Ok::<_, ()>(None);
+            }
+
+            let end = (start + 3).min(10);
+            Ok(Some(((start..end).collect(), end)))
+        });
+
+        assert_eq!(
+            futures_lite::future::block_on(stream.try_collect_all()),
+            Ok((0..10).collect())
+        );
+    }
+
+    #[test]
+    fn test_unfold_error() {
+        let stream = PaginatedStream::unfold(0_u32, |page| async move {
+            match page {
+                0 => Ok(Some((vec!["a", "b"], 1))),
+                _ => Err(page),
+            }
+        });
+
+        let results = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+        assert_eq!(results, [Ok("a"), Ok("b"), Err(1)]);
+    }
+}
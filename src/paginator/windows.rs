@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use super::{PaginatedStream, PaginationDelegate};
+
+/// Yields overlapping windows of consecutive items from a [`PaginatedStream`].
+/// Construct this with [`PaginatedStream::windows`].
+pub struct Windows<'f, D>
+where
+    D: PaginationDelegate,
+{
+    stream: PaginatedStream<'f, D>,
+    size: usize,
+    window: VecDeque<D::Item>,
+}
+
+impl<'f, D> PaginatedStream<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Clone + Unpin,
+{
+    /// Yield every window of `size` consecutive items, each overlapping the
+    /// previous by all but one item, the same as [`slice::windows`]. The
+    /// windows span the boundaries between pages, so the items of a page are
+    /// compared with those of the page before it. This is useful for computing
+    /// the differences between adjacent items, such as the time between
+    /// events.
+    ///
+    /// Each window is yielded as soon as the item that completes it has been
+    /// received. If there are fewer than `size` items in total, no window is
+    /// yielded at all. Errors are yielded as usual, and the window in progress
+    /// is kept, although the stream will most likely close after one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn windows(self, size: usize) -> Windows<'f, D> {
+        assert!(size != 0, "window size must be non-zero");
+
+        Windows {
+            stream: self,
+            size,
+            window: VecDeque::with_capacity(size),
+        }
+    }
+}
+
+impl<'f, D> Windows<'f, D>
+where
+    D: PaginationDelegate,
+{
+    /// Consume this, taking out the stream that the windows are taken from.
+    /// The items of the window in progress are discarded.
+    pub fn into_inner(self) -> PaginatedStream<'f, D> {
+        self.stream
+    }
+}
+
+impl<'f, D> Unpin for Windows<'f, D>
+where
+    D: PaginationDelegate + Unpin,
+    D::Item: Unpin,
+{
+}
+
+impl<'f, D> Stream for Windows<'f, D>
+where
+    D: 'f + PaginationDelegate + Unpin,
+    D::Item: Clone + Unpin,
+{
+    type Item = Result<Vec<D::Item>, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            match Pin::new(&mut this.stream).poll_next(ctx) {
+                Poll::Ready(Some(Ok(item))) => {
+                    if this.window.len() == this.size {
+                        this.window.pop_front();
+                    }
+                    this.window.push_back(item);
+
+                    if this.window.len() == this.size {
+                        return Poll::Ready(Some(Ok(this.window.iter().cloned().collect())));
+                    }
+                }
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.stream.size_hint();
+        // The number of items that must still be received before the first
+        // window is complete, which is zero once it has been yielded.
+        let filling = (self.size - 1).saturating_sub(self.window.len());
+
+        (
+            lower.saturating_sub(filling),
+            upper.map(|upper| upper.saturating_sub(filling)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::StreamExt;
+
+    use crate::paginator::tests::NumbersDelegate;
+    use crate::paginator::PaginatedStream;
+
+    #[test]
+    fn test_windows() {
+        // Two pages, so the third window spans the boundary between them.
+        let stream = PaginatedStream::from(NumbersDelegate::new(3, 6)).windows(2);
+
+        let windows = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+        assert_eq!(
+            windows,
+            [
+                Ok(vec![0, 1]),
+                Ok(vec![1, 2]),
+                Ok(vec![2, 3]),
+                Ok(vec![3, 4]),
+                Ok(vec![4, 5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_windows_too_few_items() {
+        let stream = PaginatedStream::from(NumbersDelegate::new(2, 3)).windows(4);
+
+        let windows = futures_lite::future::block_on(stream.collect::<Vec<_>>());
+        assert!(windows.is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+//! The policy for retrying something that failed, which is shared by the
+//! retries of pages in `paginator` and of requests in `endpoints`.
+
+use std::time::Duration;
+
+/// Determines how many times, and how often, something that failed is tried
+/// again before giving up on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    attempts: usize,
+    delay: Duration,
+    backoff: u32,
+}
+
+impl RetryPolicy {
+    /// Retry up to `attempts` times (after the first failure), immediately.
+    pub fn new(attempts: usize) -> Self {
+        Self {
+            attempts,
+            delay: Duration::ZERO,
+            backoff: 1,
+        }
+    }
+
+    /// Wait for `delay` before the first retry.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Multiply the delay by `factor` after every retry, for exponential
+    /// backoff. The default factor is `1`, which keeps the delay constant.
+    pub fn with_backoff(mut self, factor: u32) -> Self {
+        self.backoff = factor;
+        self
+    }
+
+    /// The maximum number of retries.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// The time to wait before the retry numbered `attempt`, starting from
+    /// zero for the first retry.
+    pub fn delay_for(&self, attempt: usize) -> Duration {
+        let factor = u32::try_from(attempt)
+            .ok()
+            .and_then(|attempt| self.backoff.checked_pow(attempt))
+            .unwrap_or(u32::MAX);
+
+        self.delay.saturating_mul(factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RetryPolicy;
+
+    #[test]
+    fn test_delay_for() {
+        let policy = RetryPolicy::new(4)
+            .with_delay(Duration::from_millis(100))
+            .with_backoff(2);
+
+        assert_eq!(policy.delay_for(0), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(800));
+        assert_eq!(
+            policy.delay_for(usize::MAX),
+            Duration::from_millis(100) * u32::MAX
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! De/serialize `Option<T>` so that the key is always present, with `None` as
+//! an explicit `null`.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::AlwaysNull")]
+//! ```
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::AlwaysNull<...>")]
+//! ```
+//! ```rust
+//! #[serde(serialize_with = "awaur::serde_with::always_null::serialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::always_null::deserialize")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::always_null")]
+//! ```
+//!
+//! This is for APIs where a key with a value of `null` means something other
+//! than a missing key, such as when updating an object, where `null` clears a
+//! field and a missing key leaves it unchanged. `None` is always serialized as
+//! `null`, and should not be combined with `skip_serializing_if`. When
+//! deserializing, a missing key is an error, rather than becoming `None` as it
+//! would for a plain `Option`; add `#[serde(default)]` to accept it anyway.
+
+pub use with::*;
+#[doc(hidden)]
+#[cfg(feature = "serde-as-wrapper")]
+pub use wrapper::*;
+
+mod with {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// ```rust
+    /// #[serde(serialize_with = "awaur::serde_with::always_null::serialize")]
+    /// ```
+    pub fn serialize<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        match value {
+            Some(value) => serializer.serialize_some(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::always_null::deserialize")]
+    /// ```
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        Option::deserialize(deserializer)
+    }
+}
+
+#[cfg(feature = "serde-as-wrapper")]
+mod wrapper {
+    use std::marker::PhantomData;
+
+    use serde::{Deserializer, Serializer};
+    use serde_with::de::DeserializeAsWrap;
+    use serde_with::ser::SerializeAsWrap;
+    use serde_with::{DeserializeAs, Same, SerializeAs};
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs] for `Option<T>`. The inner
+    /// value is itself de/serialized with `T`, which defaults to
+    /// [`Same`][serde_with::Same].
+    pub struct AlwaysNull<T = Same>(PhantomData<T>);
+
+    impl<T, U> SerializeAs<Option<T>> for AlwaysNull<U>
+    where
+        U: SerializeAs<T>,
+    {
+        fn serialize_as<S>(source: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let source = source.as_ref().map(SerializeAsWrap::<T, U>::new);
+            super::with::serialize(&source, serializer)
+        }
+    }
+
+    impl<'de, T, U> DeserializeAs<'de, Option<T>> for AlwaysNull<U>
+    where
+        U: DeserializeAs<'de, T>,
+    {
+        fn deserialize_as<D>(deserializer: D) -> Result<Option<T>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let wrapped: Option<DeserializeAsWrap<T, U>> = super::with::deserialize(deserializer)?;
+            Ok(wrapped.map(DeserializeAsWrap::into_inner))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde-as-wrapper"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_with::{serde_as, DisplayFromStr};
+
+    use super::AlwaysNull;
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct ProjectUpdate {
+        #[serde_as(as = "AlwaysNull")]
+        description: Option<String>,
+        #[serde_as(as = "AlwaysNull<DisplayFromStr>")]
+        downloads: Option<u64>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct WithProjectUpdate {
+        #[serde(with = "super")]
+        description: Option<String>,
+    }
+
+    #[test]
+    fn test_always_present() {
+        let update = ProjectUpdate {
+            description: None,
+            downloads: Some(12),
+        };
+        let json = r#"{"description":null,"downloads":"12"}"#;
+
+        assert_eq!(serde_json::to_string(&update).unwrap(), json);
+        assert_eq!(serde_json::from_str::<ProjectUpdate>(json).unwrap(), update);
+
+        let update = WithProjectUpdate {
+            description: Some("hello".to_owned()),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&update).unwrap(),
+            r#"{"description":"hello"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&WithProjectUpdate { description: None }).unwrap(),
+            r#"{"description":null}"#
+        );
+    }
+
+    #[test]
+    fn test_missing_key() {
+        assert!(serde_json::from_str::<WithProjectUpdate>("{}").is_err());
+        assert_eq!(
+            serde_json::from_str::<WithProjectUpdate>(r#"{"description":null}"#).unwrap(),
+            WithProjectUpdate { description: None }
+        );
+    }
+}
@@ -13,6 +13,30 @@
 //! ```rust
 //! #[serde(with = "awaur::serde_with::base62")]
 //! ```
+//!
+//! Signed integers, where `T: Into<i128>, i128: TryInto<T>`, are supported by
+//! the [`signed`] module and the `Base62Signed` wrapper. Zero and positive
+//! values are encoded exactly as they would be if they were unsigned, and
+//! negative values are encoded as their magnitude, prefixed with a `-`.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::Base62Signed<...>")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::base62::signed")]
+//! ```
+//!
+//! With the `serde-with-base62-cache` feature, the [`cached`] module and the
+//! `Base62Cached` wrapper serialize unsigned integers the same way, but keep
+//! the most recently encoded strings, so that serializing the same values
+//! over and over doesn't encode them every time.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::Base62Cached<...>")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::base62::cached")]
+//! ```
 
 pub use with::*;
 #[doc(hidden)]
@@ -70,6 +94,169 @@ mod with {
 
         deserializer.deserialize_str(_Visitor(PhantomData))
     }
+
+    /// The same functions, but for signed integers.
+    ///
+    /// ```rust
+    /// #[serde(with = "awaur::serde_with::base62::signed")]
+    /// ```
+    pub mod signed {
+        use std::fmt;
+        use std::marker::PhantomData;
+
+        use serde::de::{Error as DeserializeError, Unexpected, Visitor};
+        use serde::{Deserializer, Serializer};
+
+        /// ```rust
+        /// #[serde(serialize_with = "awaur::serde_with::base62::signed::serialize")]
+        /// ```
+        pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+            T: Clone + Into<i128>,
+        {
+            let value: i128 = value.clone().into();
+            let encoded = base62::encode(value.unsigned_abs());
+
+            if value < 0 {
+                serializer.serialize_str(&format!("-{}", encoded))
+            } else {
+                serializer.serialize_str(&encoded)
+            }
+        }
+
+        /// ```rust
+        /// #[serde(deserialize_with = "awaur::serde_with::base62::signed::deserialize")]
+        /// ```
+        pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+        where
+            D: Deserializer<'de>,
+            i128: TryInto<T>,
+        {
+            struct _Visitor<T>(PhantomData<T>);
+
+            impl<'de, T> Visitor<'de> for _Visitor<T>
+            where
+                i128: TryInto<T>,
+            {
+                type Value = T;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str(
+                        "a value that can be converted from a base-62 encoded i128, \
+                         optionally prefixed with a `-`",
+                    )
+                }
+
+                fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+                where
+                    E: DeserializeError,
+                {
+                    let (negative, magnitude) = match value.strip_prefix('-') {
+                        Some(magnitude) => (true, magnitude),
+                        None => (false, value),
+                    };
+                    let magnitude = base62::decode(magnitude).map_err(DeserializeError::custom)?;
+                    // The magnitude of `i128::MIN` is one more than that of
+                    // `i128::MAX`, so it has to be subtracted from zero.
+                    let signed = if negative {
+                        0i128.checked_sub_unsigned(magnitude)
+                    } else {
+                        i128::try_from(magnitude).ok()
+                    };
+
+                    signed
+                        .and_then(|signed| signed.try_into().ok())
+                        .ok_or_else(|| {
+                            DeserializeError::invalid_value(Unexpected::Str(value), &self)
+                        })
+                }
+            }
+
+            deserializer.deserialize_str(_Visitor(PhantomData))
+        }
+    }
+
+    /// The same functions, but remembering the strings that values were
+    /// most recently encoded as, in a cache of up to [`CAPACITY`] entries per
+    /// thread, from which the least recently used entry is evicted when it is
+    /// full. Deserializing is not cached.
+    ///
+    /// This is only beneficial when the same values are serialized many
+    /// times, such as a small set of IDs that appear in every response of a
+    /// server. For values that rarely repeat, every value misses the cache,
+    /// which is slower than just encoding it.
+    ///
+    /// ```rust
+    /// #[serde(with = "awaur::serde_with::base62::cached")]
+    /// ```
+    #[cfg(feature = "serde-with-base62-cache")]
+    pub mod cached {
+        use std::cell::RefCell;
+        use std::collections::HashMap;
+
+        use serde::Serializer;
+
+        pub use super::deserialize;
+
+        /// The maximum number of encoded strings kept by each thread.
+        pub const CAPACITY: usize = 256;
+
+        /// The encoded strings, and when each was last used.
+        #[derive(Default)]
+        struct Cache {
+            entries: HashMap<u128, (String, u64)>,
+            clock: u64,
+        }
+
+        thread_local! {
+            static CACHE: RefCell<Cache> = RefCell::default();
+            #[cfg(test)]
+            pub(crate) static ENCODES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+        }
+
+        /// ```rust
+        /// #[serde(serialize_with = "awaur::serde_with::base62::cached::serialize")]
+        /// ```
+        pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+            T: Clone + Into<u128>,
+        {
+            CACHE.with(|cache| {
+                let mut cache = cache.borrow_mut();
+                let value = value.clone().into();
+                cache.clock += 1;
+                let clock = cache.clock;
+
+                if let Some((encoded, used)) = cache.entries.get_mut(&value) {
+                    *used = clock;
+                    return serializer.serialize_str(encoded);
+                }
+
+                // Finding the least recently used entry is linear, but it is
+                // only done when a value misses, and the cache is full.
+                if cache.entries.len() >= CAPACITY {
+                    let oldest = cache
+                        .entries
+                        .iter()
+                        .min_by_key(|(_, (_, used))| *used)
+                        .map(|(value, _)| *value);
+                    if let Some(oldest) = oldest {
+                        cache.entries.remove(&oldest);
+                    }
+                }
+
+                #[cfg(test)]
+                ENCODES.with(|encodes| encodes.set(encodes.get() + 1));
+                let encoded = base62::encode(value);
+                let result = serializer.serialize_str(&encoded);
+                cache.entries.insert(value, (encoded, clock));
+
+                result
+            })
+        }
+    }
 }
 
 #[cfg(feature = "serde-as-wrapper")]
@@ -81,6 +268,16 @@ mod wrapper {
     /// [`DeserializeAs`][serde_with::DeserializeAs].
     pub struct Base62;
 
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs] for signed integers.
+    pub struct Base62Signed;
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs], caching the encoded
+    /// strings. See the [`cached`][super::cached] module.
+    #[cfg(feature = "serde-with-base62-cache")]
+    pub struct Base62Cached;
+
     impl<T> SerializeAs<T> for Base62
     where
         T: Clone + Into<u128>,
@@ -104,6 +301,56 @@ mod wrapper {
             super::with::deserialize(deserializer)
         }
     }
+
+    impl<T> SerializeAs<T> for Base62Signed
+    where
+        T: Clone + Into<i128>,
+    {
+        fn serialize_as<S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::signed::serialize(source, serializer)
+        }
+    }
+
+    impl<'de, T> DeserializeAs<'de, T> for Base62Signed
+    where
+        i128: TryInto<T>,
+    {
+        fn deserialize_as<D>(deserializer: D) -> Result<T, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::signed::deserialize(deserializer)
+        }
+    }
+
+    #[cfg(feature = "serde-with-base62-cache")]
+    impl<T> SerializeAs<T> for Base62Cached
+    where
+        T: Clone + Into<u128>,
+    {
+        fn serialize_as<S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::cached::serialize(source, serializer)
+        }
+    }
+
+    #[cfg(feature = "serde-with-base62-cache")]
+    impl<'de, T> DeserializeAs<'de, T> for Base62Cached
+    where
+        u128: TryInto<T>,
+    {
+        fn deserialize_as<D>(deserializer: D) -> Result<T, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::cached::deserialize(deserializer)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -111,7 +358,7 @@ pub mod tests {
     use serde::{Deserialize, Serialize};
     use serde_with::serde_as;
 
-    use super::Base62;
+    use super::{Base62, Base62Signed};
 
     #[serde_as]
     #[derive(Serialize, Deserialize)]
@@ -156,4 +403,70 @@ pub mod tests {
             assert_eq!(&expect, &actual);
         }
     }
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct SignedType {
+        #[serde_as(as = "Vec<Base62Signed>")]
+        pub values: Vec<i64>,
+    }
+
+    #[test]
+    fn test_signed_roundtrip() {
+        let container = SignedType {
+            values: vec![0, 61, 62, -1, -62, i64::MAX, i64::MIN],
+        };
+        let serialized = serde_json::to_string(&container).unwrap();
+
+        // Positive values are encoded the same as they would be if unsigned.
+        assert!(serialized.starts_with(r#"{"values":["0","z","10","-1","-10","#));
+        assert!(serialized.contains(&format!(r#""{}""#, base62::encode(i64::MAX as u64))));
+        assert_eq!(
+            serde_json::from_str::<SignedType>(&serialized).unwrap(),
+            container
+        );
+
+        // Values that don't fit in the type are rejected rather than wrapped.
+        let out_of_range = format!(r#"{{"values":["-{}"]}}"#, base62::encode(1u128 << 63 | 1));
+        assert!(serde_json::from_str::<SignedType>(&out_of_range).is_err());
+    }
+
+    #[cfg(feature = "serde-with-base62-cache")]
+    #[serde_as]
+    #[derive(Serialize)]
+    struct CachedType {
+        #[serde_as(as = "Vec<super::Base62Cached>")]
+        pub values: Vec<u64>,
+    }
+
+    #[cfg(feature = "serde-with-base62-cache")]
+    #[test]
+    fn test_cached_encodes() {
+        use super::cached::{CAPACITY, ENCODES};
+
+        // The same few IDs, over and over, as in the responses of a server.
+        let container = CachedType {
+            values: (0..1000).map(|index| u64::MAX - index % 10).collect(),
+        };
+        let uncached = TestType {
+            values: container.values.clone(),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&container).unwrap(),
+            serde_json::to_string(&uncached).unwrap()
+        );
+        // Each distinct value was only encoded the first time it was seen.
+        assert_eq!(ENCODES.with(|encodes| encodes.get()), 10);
+
+        // More distinct values than fit evict the least recently used ones,
+        // so the ten from before have to be encoded again.
+        let container = CachedType {
+            values: (0..CAPACITY as u64)
+                .chain(u64::MAX - 9..=u64::MAX)
+                .collect(),
+        };
+        serde_json::to_string(&container).unwrap();
+        assert_eq!(ENCODES.with(|encodes| encodes.get()), 10 + CAPACITY + 10);
+    }
 }
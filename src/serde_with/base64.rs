@@ -0,0 +1,255 @@
+//! De/serialize bytes as a base-64-encoded string, where `T: AsRef<[u8]>` when
+//! serializing, and bytes are deserialized into a `Vec<u8>`.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::Base64")]
+//! ```
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::Base64UrlSafe")]
+//! ```
+//! ```rust
+//! #[serde(serialize_with = "awaur::serde_with::base64::serialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::base64::deserialize")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::base64")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::base64::url_safe")]
+//! ```
+//!
+//! The standard alphabet (with `+` and `/`) is serialized with padding, and the
+//! URL-safe alphabet (with `-` and `_`) is serialized without it, as it is in
+//! URLs and tokens. Both accept strings with or without padding when
+//! deserializing.
+
+pub use with::*;
+#[doc(hidden)]
+#[cfg(feature = "serde-as-wrapper")]
+pub use wrapper::*;
+
+mod engines {
+    use ::base64::alphabet;
+    use ::base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+
+    pub const STANDARD: GeneralPurpose = GeneralPurpose::new(
+        &alphabet::STANDARD,
+        GeneralPurposeConfig::new()
+            .with_encode_padding(true)
+            .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+
+    pub const URL_SAFE: GeneralPurpose = GeneralPurpose::new(
+        &alphabet::URL_SAFE,
+        GeneralPurposeConfig::new()
+            .with_encode_padding(false)
+            .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+}
+
+mod with {
+    use std::fmt;
+
+    use ::base64::engine::GeneralPurpose;
+    use ::base64::Engine;
+    use serde::de::{Error as DeserializeError, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    use super::engines;
+
+    /// ```rust
+    /// #[serde(serialize_with = "awaur::serde_with::base64::serialize")]
+    /// ```
+    pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: AsRef<[u8]>,
+    {
+        serialize_with(&engines::STANDARD, value, serializer)
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::base64::deserialize")]
+    /// ```
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_with(&engines::STANDARD, deserializer)
+    }
+
+    /// The same functions, but with the URL-safe alphabet.
+    ///
+    /// ```rust
+    /// #[serde(with = "awaur::serde_with::base64::url_safe")]
+    /// ```
+    pub mod url_safe {
+        use serde::{Deserializer, Serializer};
+
+        use super::super::engines;
+
+        /// ```rust
+        /// #[serde(serialize_with = "awaur::serde_with::base64::url_safe::serialize")]
+        /// ```
+        pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+            T: AsRef<[u8]>,
+        {
+            super::serialize_with(&engines::URL_SAFE, value, serializer)
+        }
+
+        /// ```rust
+        /// #[serde(deserialize_with = "awaur::serde_with::base64::url_safe::deserialize")]
+        /// ```
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::deserialize_with(&engines::URL_SAFE, deserializer)
+        }
+    }
+
+    pub(super) fn serialize_with<S, T>(
+        engine: &GeneralPurpose,
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: AsRef<[u8]>,
+    {
+        serializer.serialize_str(&engine.encode(value))
+    }
+
+    pub(super) fn deserialize_with<'de, D>(
+        engine: &GeneralPurpose,
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct _Visitor<'a>(&'a GeneralPurpose);
+
+        impl<'de, 'a> Visitor<'de> for _Visitor<'a> {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a base-64 encoded string")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: DeserializeError,
+            {
+                self.0.decode(value).map_err(DeserializeError::custom)
+            }
+        }
+
+        deserializer.deserialize_str(_Visitor(engine))
+    }
+}
+
+#[cfg(feature = "serde-as-wrapper")]
+mod wrapper {
+    use serde::{Deserializer, Serializer};
+    use serde_with::{DeserializeAs, SerializeAs};
+
+    use super::engines;
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs] with the standard alphabet.
+    pub struct Base64;
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs] with the URL-safe alphabet.
+    pub struct Base64UrlSafe;
+
+    impl<T> SerializeAs<T> for Base64
+    where
+        T: AsRef<[u8]>,
+    {
+        fn serialize_as<S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::serialize_with(&engines::STANDARD, source, serializer)
+        }
+    }
+
+    impl<'de> DeserializeAs<'de, Vec<u8>> for Base64 {
+        fn deserialize_as<D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::deserialize_with(&engines::STANDARD, deserializer)
+        }
+    }
+
+    impl<T> SerializeAs<T> for Base64UrlSafe
+    where
+        T: AsRef<[u8]>,
+    {
+        fn serialize_as<S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::serialize_with(&engines::URL_SAFE, source, serializer)
+        }
+    }
+
+    impl<'de> DeserializeAs<'de, Vec<u8>> for Base64UrlSafe {
+        fn deserialize_as<D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::deserialize_with(&engines::URL_SAFE, deserializer)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde-as-wrapper"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_with::serde_as;
+
+    use super::{Base64, Base64UrlSafe};
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Blob {
+        #[serde_as(as = "Base64")]
+        standard: Vec<u8>,
+        #[serde_as(as = "Base64UrlSafe")]
+        url_safe: Vec<u8>,
+        #[serde(with = "super::url_safe")]
+        with_url_safe: Vec<u8>,
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        // These bytes encode to characters that differ between the alphabets.
+        let bytes = vec![0xfb, 0xff, 0xbf, 0x01];
+        let blob = Blob {
+            standard: bytes.clone(),
+            url_safe: bytes.clone(),
+            with_url_safe: bytes,
+        };
+        let json = r#"{"standard":"+/+/AQ==","url_safe":"-_-_AQ","with_url_safe":"-_-_AQ"}"#;
+
+        assert_eq!(serde_json::to_string(&blob).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Blob>(json).unwrap(), blob);
+    }
+
+    #[test]
+    fn test_padding_and_alphabet() {
+        // Padding is optional either way, but the alphabets are not mixed.
+        let json = r#"{"standard":"+/+/AQ","url_safe":"-_-_AQ==","with_url_safe":"-_-_AQ"}"#;
+        assert!(serde_json::from_str::<Blob>(json).is_ok());
+
+        let json = r#"{"standard":"-_-_AQ==","url_safe":"-_-_AQ","with_url_safe":"-_-_AQ"}"#;
+        assert!(serde_json::from_str::<Blob>(json).is_err());
+    }
+}
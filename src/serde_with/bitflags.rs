@@ -0,0 +1,333 @@
+//! De/serialize `Vec<T>` as an integer bit mask, where `T: BitFlag`.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::BitFlags")]
+//! ```
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::BitFlagsPreserving")]
+//! ```
+//! ```rust
+//! #[serde(serialize_with = "awaur::serde_with::bitflags::serialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::bitflags::deserialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::bitflags::deserialize_preserving")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::bitflags")]
+//! ```
+//!
+//! Bits in the mask that do not belong to any of [`BitFlag::FLAGS`] are
+//! handled in one of two ways. The plain [`deserialize`] (and [`BitFlags`])
+//! rejects them with an error. The [`deserialize_preserving`] function (and
+//! [`BitFlagsPreserving`]) passes them to [`BitFlag::from_unknown`], so that
+//! they can be kept in a catch-all variant and serialized back unchanged.
+
+pub use with::*;
+#[doc(hidden)]
+#[cfg(feature = "serde-as-wrapper")]
+pub use wrapper::*;
+
+/// Implemented by a type, usually a field-less enum, where each value
+/// represents one bit (or several) of an integer bit mask.
+pub trait BitFlag: Clone + Sized + 'static {
+    /// Every known flag. When deserializing, the flags are checked in this
+    /// order, and the resulting vector will have the same order.
+    const FLAGS: &'static [Self];
+
+    /// The bits of the mask that this flag represents.
+    fn bits(&self) -> u64;
+
+    /// Construct a flag that holds the `bits` of a mask that are not covered
+    /// by any of the [`BitFlag::FLAGS`], so that they are not lost. This is
+    /// only used when deserializing with [`deserialize_preserving`]. The
+    /// default implementation returns `None`, which makes unknown bits an
+    /// error regardless.
+    fn from_unknown(bits: u64) -> Option<Self> {
+        let _ = bits;
+        None
+    }
+}
+
+mod with {
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    use serde::de::{Error as DeserializeError, Unexpected, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    use super::BitFlag;
+
+    /// ```rust
+    /// #[serde(serialize_with = "awaur::serde_with::bitflags::serialize")]
+    /// ```
+    pub fn serialize<S, T>(value: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: BitFlag,
+    {
+        serializer.serialize_u64(value.iter().fold(0, |mask, flag| mask | flag.bits()))
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::bitflags::deserialize")]
+    /// ```
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: BitFlag,
+    {
+        deserializer.deserialize_u64(_Visitor {
+            preserve: false,
+            marker: PhantomData,
+        })
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::bitflags::deserialize_preserving")]
+    /// ```
+    pub fn deserialize_preserving<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: BitFlag,
+    {
+        deserializer.deserialize_u64(_Visitor {
+            preserve: true,
+            marker: PhantomData,
+        })
+    }
+
+    struct _Visitor<T> {
+        preserve: bool,
+        marker: PhantomData<T>,
+    }
+
+    impl<'de, T> Visitor<'de> for _Visitor<T>
+    where
+        T: BitFlag,
+    {
+        type Value = Vec<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            if self.preserve {
+                formatter.write_str("an integer bit mask")
+            } else {
+                formatter.write_str("an integer bit mask of only known flags")
+            }
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+        where
+            E: DeserializeError,
+        {
+            let mut flags = Vec::new();
+            let mut unknown = value;
+
+            for flag in T::FLAGS {
+                let bits = flag.bits();
+
+                if bits != 0 && value & bits == bits {
+                    flags.push(flag.clone());
+                    unknown &= !bits;
+                }
+            }
+
+            if unknown != 0 {
+                match self.preserve.then(|| T::from_unknown(unknown)).flatten() {
+                    Some(flag) => flags.push(flag),
+                    None => {
+                        return Err(DeserializeError::invalid_value(
+                            Unexpected::Unsigned(value),
+                            &self,
+                        ))
+                    }
+                }
+            }
+
+            Ok(flags)
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+        where
+            E: DeserializeError,
+        {
+            match u64::try_from(value) {
+                Ok(value) => self.visit_u64(value),
+                Err(_) => Err(DeserializeError::invalid_value(
+                    Unexpected::Signed(value),
+                    &self,
+                )),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde-as-wrapper")]
+mod wrapper {
+    use serde::{Deserializer, Serializer};
+    use serde_with::{DeserializeAs, SerializeAs};
+
+    use super::BitFlag;
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs], rejecting unknown bits.
+    pub struct BitFlags;
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs], preserving unknown bits
+    /// with [`BitFlag::from_unknown`].
+    pub struct BitFlagsPreserving;
+
+    impl<T> SerializeAs<Vec<T>> for BitFlags
+    where
+        T: BitFlag,
+    {
+        fn serialize_as<S>(source: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::serialize(source, serializer)
+        }
+    }
+
+    impl<'de, T> DeserializeAs<'de, Vec<T>> for BitFlags
+    where
+        T: BitFlag,
+    {
+        fn deserialize_as<D>(deserializer: D) -> Result<Vec<T>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::deserialize(deserializer)
+        }
+    }
+
+    impl<T> SerializeAs<Vec<T>> for BitFlagsPreserving
+    where
+        T: BitFlag,
+    {
+        fn serialize_as<S>(source: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::serialize(source, serializer)
+        }
+    }
+
+    impl<'de, T> DeserializeAs<'de, Vec<T>> for BitFlagsPreserving
+    where
+        T: BitFlag,
+    {
+        fn deserialize_as<D>(deserializer: D) -> Result<Vec<T>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::deserialize_preserving(deserializer)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde-as-wrapper"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_with::serde_as;
+
+    use super::{BitFlag, BitFlags, BitFlagsPreserving};
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Permission {
+        Read,
+        Write,
+        Delete,
+        Admin,
+        Unknown(u64),
+    }
+
+    impl BitFlag for Permission {
+        const FLAGS: &'static [Self] = &[
+            Permission::Read,
+            Permission::Write,
+            Permission::Delete,
+            Permission::Admin,
+        ];
+
+        fn bits(&self) -> u64 {
+            match self {
+                Permission::Read => 1 << 0,
+                Permission::Write => 1 << 1,
+                Permission::Delete => 1 << 2,
+                Permission::Admin => 1 << 7,
+                Permission::Unknown(bits) => *bits,
+            }
+        }
+
+        fn from_unknown(bits: u64) -> Option<Self> {
+            Some(Permission::Unknown(bits))
+        }
+    }
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Member {
+        #[serde_as(as = "BitFlags")]
+        permissions: Vec<Permission>,
+    }
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct LenientMember {
+        #[serde_as(as = "BitFlagsPreserving")]
+        permissions: Vec<Permission>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct WithMember {
+        #[serde(with = "super")]
+        permissions: Vec<Permission>,
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let json = r#"{"permissions":135}"#;
+        let member = serde_json::from_str::<Member>(json).unwrap();
+
+        assert_eq!(
+            member.permissions,
+            [
+                Permission::Read,
+                Permission::Write,
+                Permission::Delete,
+                Permission::Admin
+            ]
+        );
+        assert_eq!(serde_json::to_string(&member).unwrap(), json);
+
+        // The plain functions should behave the same as the wrapper.
+        let member = serde_json::from_str::<WithMember>(r#"{"permissions":5}"#).unwrap();
+
+        assert_eq!(member.permissions, [Permission::Read, Permission::Delete]);
+        assert_eq!(
+            serde_json::to_string(&member).unwrap(),
+            r#"{"permissions":5}"#
+        );
+    }
+
+    #[test]
+    fn test_unknown_bits() {
+        // Bit 4 (16) is not a known permission.
+        let json = r#"{"permissions":19}"#;
+
+        assert!(serde_json::from_str::<Member>(json).is_err());
+        assert!(serde_json::from_str::<WithMember>(json).is_err());
+
+        let member = serde_json::from_str::<LenientMember>(json).unwrap();
+
+        assert_eq!(
+            member.permissions,
+            [Permission::Read, Permission::Write, Permission::Unknown(16)]
+        );
+        assert_eq!(serde_json::to_string(&member).unwrap(), json);
+    }
+}
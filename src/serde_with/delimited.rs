@@ -0,0 +1,213 @@
+//! De/serialize a `Vec<T>` as a single string of its items joined by a
+//! delimiter, where `T: Display` when serializing and `T: FromStr` when
+//! deserializing. This is how many APIs expect an array in a query string,
+//! such as `ids=1,2,3`, rather than repeating the key for every item.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::CommaSeparated")]
+//! ```
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::Delimited<'|'>")]
+//! ```
+//! ```rust
+//! #[serde(serialize_with = "awaur::serde_with::delimited::serialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::delimited::deserialize")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::delimited")]
+//! ```
+//!
+//! The functions always use a comma; use the wrapper for any other delimiter.
+//! An empty vector is serialized as an empty string, and an empty string is
+//! deserialized as an empty vector. Items are not escaped, so an item that
+//! contains the delimiter when formatted will be split into two when
+//! deserialized.
+
+pub use with::*;
+#[doc(hidden)]
+#[cfg(feature = "serde-as-wrapper")]
+pub use wrapper::*;
+
+mod with {
+    use std::fmt::{self, Display};
+    use std::marker::PhantomData;
+    use std::str::FromStr;
+
+    use serde::de::{Error as DeserializeError, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    /// ```rust
+    /// #[serde(serialize_with = "awaur::serde_with::delimited::serialize")]
+    /// ```
+    pub fn serialize<S, T>(value: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Display,
+    {
+        serialize_with(',', value, serializer)
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::delimited::deserialize")]
+    /// ```
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        deserialize_with(',', deserializer)
+    }
+
+    pub(super) fn serialize_with<S, T>(
+        delimiter: char,
+        value: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Display,
+    {
+        let joined = value
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(delimiter.encode_utf8(&mut [0; 4]));
+
+        serializer.serialize_str(&joined)
+    }
+
+    pub(super) fn deserialize_with<'de, D, T>(
+        delimiter: char,
+        deserializer: D,
+    ) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        struct _Visitor<T>(char, PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for _Visitor<T>
+        where
+            T: FromStr,
+            T::Err: Display,
+        {
+            type Value = Vec<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a string of items separated by {:?}", self.0)
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: DeserializeError,
+            {
+                if value.is_empty() {
+                    return Ok(Vec::new());
+                }
+
+                value
+                    .split(self.0)
+                    .map(|item| {
+                        item.parse().map_err(|error| {
+                            DeserializeError::custom(format_args!(
+                                "invalid item {:?}: {}",
+                                item, error
+                            ))
+                        })
+                    })
+                    .collect()
+            }
+        }
+
+        deserializer.deserialize_str(_Visitor(delimiter, PhantomData))
+    }
+}
+
+#[cfg(feature = "serde-as-wrapper")]
+mod wrapper {
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    use serde::{Deserializer, Serializer};
+    use serde_with::{DeserializeAs, SerializeAs};
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs] for `Vec<T>`, with the
+    /// items separated by `DELIMITER`.
+    pub struct Delimited<const DELIMITER: char>;
+
+    /// Shorthand for [`Delimited`] with a comma.
+    pub type CommaSeparated = Delimited<','>;
+
+    impl<T, const DELIMITER: char> SerializeAs<Vec<T>> for Delimited<DELIMITER>
+    where
+        T: Display,
+    {
+        fn serialize_as<S>(source: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::serialize_with(DELIMITER, source, serializer)
+        }
+    }
+
+    impl<'de, T, const DELIMITER: char> DeserializeAs<'de, Vec<T>> for Delimited<DELIMITER>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        fn deserialize_as<D>(deserializer: D) -> Result<Vec<T>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::deserialize_with(DELIMITER, deserializer)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde-as-wrapper"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_with::serde_as;
+
+    use super::{CommaSeparated, Delimited};
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct SearchParams {
+        #[serde_as(as = "CommaSeparated")]
+        ids: Vec<u32>,
+        #[serde_as(as = "Delimited<'|'>")]
+        tags: Vec<String>,
+        #[serde(with = "super")]
+        sort: Vec<String>,
+    }
+
+    #[test]
+    fn test_query_string() {
+        let params = SearchParams {
+            ids: vec![1, 2, 3],
+            tags: vec!["a,b".to_owned(), "c".to_owned()],
+            sort: Vec::new(),
+        };
+        let query = serde_urlencoded::to_string(&params).unwrap();
+
+        assert_eq!(query, "ids=1%2C2%2C3&tags=a%2Cb%7Cc&sort=");
+        assert_eq!(
+            serde_urlencoded::from_str::<SearchParams>(&query).unwrap(),
+            params
+        );
+    }
+
+    #[test]
+    fn test_invalid_item() {
+        let json = r#"{"ids":"1,x,3","tags":"","sort":""}"#;
+        let error = serde_json::from_str::<SearchParams>(json).unwrap_err();
+
+        assert!(error.to_string().starts_with(r#"invalid item "x""#));
+    }
+}
@@ -0,0 +1,247 @@
+//! De/serialize bytes as a hexadecimal string, where `T: AsRef<[u8]>` when
+//! serializing, and bytes are deserialized into a `Vec<u8>`.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::Hex")]
+//! ```
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::HexUppercase")]
+//! ```
+//! ```rust
+//! #[serde(serialize_with = "awaur::serde_with::hex::serialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::hex::deserialize")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::hex")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::hex::uppercase")]
+//! ```
+//!
+//! Bytes are serialized with lowercase digits, unless the uppercase variant is
+//! used. Either case (or a mix) is accepted when deserializing, but a string
+//! with an odd number of digits, or any other character, is rejected.
+
+pub use with::*;
+#[doc(hidden)]
+#[cfg(feature = "serde-as-wrapper")]
+pub use wrapper::*;
+
+mod with {
+    use std::fmt;
+
+    use serde::de::{Error as DeserializeError, Unexpected, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    const LOWERCASE: &[u8; 16] = b"0123456789abcdef";
+    const UPPERCASE: &[u8; 16] = b"0123456789ABCDEF";
+
+    /// ```rust
+    /// #[serde(serialize_with = "awaur::serde_with::hex::serialize")]
+    /// ```
+    pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: AsRef<[u8]>,
+    {
+        serialize_with(LOWERCASE, value, serializer)
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::hex::deserialize")]
+    /// ```
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct _Visitor;
+
+        impl<'de> Visitor<'de> for _Visitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string of an even number of hexadecimal digits")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: DeserializeError,
+            {
+                let invalid = || DeserializeError::invalid_value(Unexpected::Str(value), &self);
+
+                if !value.len().is_multiple_of(2) {
+                    return Err(invalid());
+                }
+
+                value
+                    .as_bytes()
+                    .chunks_exact(2)
+                    .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+                    .collect::<Option<_>>()
+                    .ok_or_else(invalid)
+            }
+        }
+
+        deserializer.deserialize_str(_Visitor)
+    }
+
+    /// The same functions, but serializing with uppercase digits.
+    ///
+    /// ```rust
+    /// #[serde(with = "awaur::serde_with::hex::uppercase")]
+    /// ```
+    pub mod uppercase {
+        use serde::Serializer;
+
+        pub use super::deserialize;
+
+        /// ```rust
+        /// #[serde(serialize_with = "awaur::serde_with::hex::uppercase::serialize")]
+        /// ```
+        pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+            T: AsRef<[u8]>,
+        {
+            super::serialize_with(super::UPPERCASE, value, serializer)
+        }
+    }
+
+    pub(super) fn serialize_with<S, T>(
+        digits: &[u8; 16],
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: AsRef<[u8]>,
+    {
+        let encoded = value
+            .as_ref()
+            .iter()
+            .flat_map(|byte| [digits[(byte >> 4) as usize], digits[(byte & 0xf) as usize]])
+            .map(char::from)
+            .collect::<String>();
+
+        serializer.serialize_str(&encoded)
+    }
+
+    fn digit(character: u8) -> Option<u8> {
+        match character {
+            b'0'..=b'9' => Some(character - b'0'),
+            b'a'..=b'f' => Some(character - b'a' + 10),
+            b'A'..=b'F' => Some(character - b'A' + 10),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde-as-wrapper")]
+mod wrapper {
+    use serde::{Deserializer, Serializer};
+    use serde_with::{DeserializeAs, SerializeAs};
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs] with lowercase digits.
+    pub struct Hex;
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs] with uppercase digits.
+    pub struct HexUppercase;
+
+    impl<T> SerializeAs<T> for Hex
+    where
+        T: AsRef<[u8]>,
+    {
+        fn serialize_as<S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::serialize(source, serializer)
+        }
+    }
+
+    impl<'de> DeserializeAs<'de, Vec<u8>> for Hex {
+        fn deserialize_as<D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::deserialize(deserializer)
+        }
+    }
+
+    impl<T> SerializeAs<T> for HexUppercase
+    where
+        T: AsRef<[u8]>,
+    {
+        fn serialize_as<S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::uppercase::serialize(source, serializer)
+        }
+    }
+
+    impl<'de> DeserializeAs<'de, Vec<u8>> for HexUppercase {
+        fn deserialize_as<D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::deserialize(deserializer)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde-as-wrapper"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_with::serde_as;
+
+    use super::{Hex, HexUppercase};
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct File {
+        #[serde_as(as = "Hex")]
+        sha256: Vec<u8>,
+        #[serde_as(as = "HexUppercase")]
+        md5: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct WithFile {
+        #[serde(with = "super")]
+        sha1: Vec<u8>,
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let file = File {
+            sha256: vec![0x00, 0x9f, 0xab, 0xff],
+            md5: vec![0x0a, 0xbc],
+        };
+        let json = r#"{"sha256":"009fabff","md5":"0ABC"}"#;
+
+        assert_eq!(serde_json::to_string(&file).unwrap(), json);
+        assert_eq!(serde_json::from_str::<File>(json).unwrap(), file);
+
+        // Either case is accepted, regardless of how it is serialized.
+        let file = serde_json::from_str::<WithFile>(r#"{"sha1":"DeadBeef"}"#).unwrap();
+        assert_eq!(file.sha1, [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(
+            serde_json::to_string(&file).unwrap(),
+            r#"{"sha1":"deadbeef"}"#
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = serde_json::from_str::<WithFile>(r#"{"sha1":"abc"}"#).unwrap_err();
+        assert!(error.to_string().starts_with("invalid value"));
+
+        let error = serde_json::from_str::<WithFile>(r#"{"sha1":"abcg"}"#).unwrap_err();
+        assert!(error.to_string().starts_with("invalid value"));
+    }
+}
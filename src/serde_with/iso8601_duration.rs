@@ -0,0 +1,297 @@
+//! De/serialize a [`Duration`] as an ISO 8601 duration string, such as
+//! `PT1H30M`.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::Iso8601Duration")]
+//! ```
+//! ```rust
+//! #[serde(serialize_with = "awaur::serde_with::iso8601_duration::serialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::iso8601_duration::deserialize")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::iso8601_duration")]
+//! ```
+//!
+//! Weeks (`W`), days (`D`), hours (`H`), minutes (`M`), and seconds (`S`) are
+//! accepted when deserializing, and only the seconds may have a fraction,
+//! separated by either a `.` or a `,`. A day is taken to be exactly 24 hours,
+//! and a week 7 days. Years and months are rejected, because they don't have
+//! a fixed length, as are negative durations and strings that are not in the
+//! format, such as `PT` with no components after it.
+//!
+//! When serializing, only hours, minutes, and seconds are written, omitting
+//! any that are zero, so a duration of 36 hours is `PT36H`, and a duration of
+//! zero is `PT0S`.
+//!
+//! [`Duration`]: std::time::Duration
+
+pub use with::*;
+#[doc(hidden)]
+#[cfg(feature = "serde-as-wrapper")]
+pub use wrapper::*;
+
+mod with {
+    use std::fmt::{self, Write};
+    use std::time::Duration;
+
+    use serde::de::{Error as DeserializeError, Unexpected, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    /// ```rust
+    /// #[serde(serialize_with = "awaur::serde_with::iso8601_duration::serialize")]
+    /// ```
+    pub fn serialize<S>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format(*value))
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::iso8601_duration::deserialize")]
+    /// ```
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct _Visitor;
+
+        impl<'de> Visitor<'de> for _Visitor {
+            type Value = Duration;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(
+                    "an ISO 8601 duration in weeks, days, hours, minutes, and seconds, such as \
+                     `PT1H30M`",
+                )
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: DeserializeError,
+            {
+                parse(value).ok_or_else(|| E::invalid_value(Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_str(_Visitor)
+    }
+
+    fn format(value: Duration) -> String {
+        let seconds = value.as_secs();
+        let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+        let nanos = value.subsec_nanos();
+        let mut formatted = String::from("PT");
+
+        // Use of unwrap:
+        // Writing to a `String` never fails.
+        if hours > 0 {
+            write!(formatted, "{}H", hours).unwrap();
+        }
+        if minutes > 0 {
+            write!(formatted, "{}M", minutes).unwrap();
+        }
+        if seconds > 0 || nanos > 0 || value.is_zero() {
+            write!(formatted, "{}", seconds).unwrap();
+            if nanos > 0 {
+                let fraction = format!("{:09}", nanos);
+                write!(formatted, ".{}", fraction.trim_end_matches('0')).unwrap();
+            }
+            formatted.push('S');
+        }
+
+        formatted
+    }
+
+    fn parse(value: &str) -> Option<Duration> {
+        let rest = value.strip_prefix('P')?;
+        let (date, time) = match rest.split_once('T') {
+            Some((date, time)) if !time.is_empty() => (date, Some(time)),
+            Some(_) => return None,
+            None => (rest, None),
+        };
+
+        if date.is_empty() && time.is_none() {
+            return None;
+        }
+
+        let mut total = Duration::ZERO;
+
+        for (date, designators) in [(date, "WD"), (time.unwrap_or_default(), "HMS")] {
+            let mut designators = designators.chars();
+            let mut rest = date;
+
+            while !rest.is_empty() {
+                let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+                let (number, designator) = (&rest[..end], rest[end..].chars().next()?);
+                rest = &rest[end + 1..];
+
+                // Each component may only appear once, and in order, which is
+                // the case if its designator is one of those remaining.
+                if !designators.any(|remaining| remaining == designator) {
+                    return None;
+                }
+
+                let unit = match designator {
+                    'W' => 7 * 86400,
+                    'D' => 86400,
+                    'H' => 3600,
+                    'M' => 60,
+                    _ => 1,
+                };
+
+                let component = match (designator, number.split_once(['.', ','])) {
+                    ('S', Some((whole, fraction))) => Duration::from_secs(parse_digits(whole)?)
+                        .checked_add(parse_fraction(fraction)?)?,
+                    (_, Some(_)) => return None,
+                    (_, None) => Duration::from_secs(parse_digits(number)?.checked_mul(unit)?),
+                };
+
+                total = total.checked_add(component)?;
+            }
+        }
+
+        Some(total)
+    }
+
+    fn parse_digits(value: &str) -> Option<u64> {
+        match !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) {
+            true => value.parse().ok(),
+            false => None,
+        }
+    }
+
+    /// Parse the digits after the decimal point of a number of seconds, with
+    /// any beyond nanoseconds truncated.
+    fn parse_fraction(value: &str) -> Option<Duration> {
+        // The digits are not parsed as a whole, because there may be more of
+        // them than fit in any integer.
+        if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+
+        let nanos = value
+            .bytes()
+            .chain(std::iter::repeat(b'0'))
+            .take(9)
+            .fold(0, |nanos, digit| nanos * 10 + u32::from(digit - b'0'));
+
+        Some(Duration::from_nanos(nanos.into()))
+    }
+}
+
+#[cfg(feature = "serde-as-wrapper")]
+mod wrapper {
+    use std::time::Duration;
+
+    use serde::{Deserializer, Serializer};
+    use serde_with::{DeserializeAs, SerializeAs};
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs].
+    pub struct Iso8601Duration;
+
+    impl SerializeAs<Duration> for Iso8601Duration {
+        fn serialize_as<S>(source: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::serialize(source, serializer)
+        }
+    }
+
+    impl<'de> DeserializeAs<'de, Duration> for Iso8601Duration {
+        fn deserialize_as<D>(deserializer: D) -> Result<Duration, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::deserialize(deserializer)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde-as-wrapper"))]
+mod tests {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Serialize};
+    use serde_with::serde_as;
+
+    use super::Iso8601Duration;
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Video {
+        #[serde_as(as = "Iso8601Duration")]
+        length: Duration,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Task {
+        #[serde(with = "super")]
+        timeout: Duration,
+    }
+
+    fn length(json: &str) -> Result<Duration, serde_json::Error> {
+        serde_json::from_str::<Video>(json).map(|video| video.length)
+    }
+
+    #[test]
+    fn test_deserialize() {
+        assert_eq!(
+            length(r#"{"length":"PT30S"}"#).unwrap(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            length(r#"{"length":"PT1H30M"}"#).unwrap(),
+            Duration::from_secs(5400)
+        );
+        assert_eq!(
+            length(r#"{"length":"P1W2DT3H4M5.25S"}"#).unwrap(),
+            Duration::from_secs(9 * 86400 + 3 * 3600 + 4 * 60 + 5) + Duration::from_millis(250)
+        );
+        assert_eq!(
+            length(r#"{"length":"PT0,5S"}"#).unwrap(),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            length(r#"{"length":"PT1.1234567891234567891234567S"}"#).unwrap(),
+            Duration::new(1, 123_456_789)
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        for value in [
+            "1H30M", "PT", "P", "P1Y", "P1M", "PT1M1H", "PT1H1H", "PT1.5H", "PT-5S", "PTS",
+            "PT1H30", "1 hour",
+        ] {
+            let error = length(&format!(r#"{{"length":"{}"}}"#, value)).unwrap_err();
+            assert!(error.to_string().starts_with("invalid value"), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_serialize() {
+        let json = |seconds: u64, nanos: u32| {
+            serde_json::to_string(&Task {
+                timeout: Duration::new(seconds, nanos),
+            })
+            .unwrap()
+        };
+
+        assert_eq!(json(30, 0), r#"{"timeout":"PT30S"}"#);
+        assert_eq!(json(5400, 0), r#"{"timeout":"PT1H30M"}"#);
+        assert_eq!(json(36 * 3600, 0), r#"{"timeout":"PT36H"}"#);
+        assert_eq!(json(61, 500_000_000), r#"{"timeout":"PT1M1.5S"}"#);
+        assert_eq!(json(0, 0), r#"{"timeout":"PT0S"}"#);
+
+        let task = Task {
+            timeout: Duration::new(3723, 40_000_000),
+        };
+        let roundtrip = serde_json::to_string(&task).unwrap();
+        assert_eq!(serde_json::from_str::<Task>(&roundtrip).unwrap(), task);
+    }
+}
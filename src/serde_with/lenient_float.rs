@@ -0,0 +1,367 @@
+//! De/serialize an `f64` that may be `NaN` or infinite, which JSON can't
+//! represent as a number.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::LenientFloat")]
+//! ```
+//! ```rust
+//! #[serde(serialize_with = "awaur::serde_with::lenient_float::serialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::lenient_float::deserialize")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::lenient_float")]
+//! ```
+//!
+//! When deserializing, a number is accepted as it is, and so is a string that
+//! parses as one, including `"NaN"`, `"Infinity"`, and `"-Infinity"`, in any
+//! case, as well as the shorter `"inf"`. `null` is accepted as `NaN`.
+//!
+//! When serializing, a finite value is written as a number, and any other as
+//! one of those three strings. To write `null` for them instead, such as for a
+//! consumer that only accepts numbers, use the [`null`] module, or the
+//! `LenientFloatNull` wrapper.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::LenientFloatNull")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::lenient_float::null")]
+//! ```
+//!
+//! Bare `NaN`, `Infinity`, and `-Infinity` literals, which some servers send
+//! even though they are not valid JSON, are rejected by `serde_json` itself
+//! while the document is parsed, before any of this is reached. To accept
+//! them, pass the body through [`quote_non_finite`] first, which turns them
+//! into the strings that are accepted here. With the `endpoints` feature, the
+//! `LenientJson` response format does this for the [`endpoint!`] macro.
+//!
+//! [`endpoint!`]: crate::endpoints::endpoint
+
+pub use with::*;
+#[doc(hidden)]
+#[cfg(feature = "serde-as-wrapper")]
+pub use wrapper::*;
+
+mod with {
+    use std::borrow::Cow;
+    use std::fmt;
+
+    use serde::de::{Error as DeserializeError, Unexpected, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    /// ```rust
+    /// #[serde(serialize_with = "awaur::serde_with::lenient_float::serialize")]
+    /// ```
+    pub fn serialize<S>(value: &f64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match non_finite_name(*value) {
+            Some(name) => serializer.serialize_str(name),
+            None => serializer.serialize_f64(*value),
+        }
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::lenient_float::deserialize")]
+    /// ```
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<f64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct _Visitor;
+
+        impl<'de> Visitor<'de> for _Visitor {
+            type Value = f64;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(
+                    "a number, `null`, or a string such as `\"NaN\"`, `\"Infinity\"`, or \
+                     `\"-Infinity\"`",
+                )
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+                Ok(value)
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(value as f64)
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(value as f64)
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: DeserializeError,
+            {
+                // This accepts `NaN`, `inf`, and `infinity` in any case, with
+                // an optional sign, in addition to numbers.
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+            }
+
+            fn visit_unit<E>(self) -> Result<Self::Value, E> {
+                Ok(f64::NAN)
+            }
+
+            fn visit_none<E>(self) -> Result<Self::Value, E> {
+                Ok(f64::NAN)
+            }
+        }
+
+        deserializer.deserialize_any(_Visitor)
+    }
+
+    /// Quote every bare `NaN`, `Infinity`, and `-Infinity` literal in a JSON
+    /// document, so that it can be parsed by `serde_json`, and the values
+    /// deserialized with this module. Literals inside of strings are left as
+    /// they are, and so is the rest of the document, which is only copied if
+    /// there is a literal to quote.
+    pub fn quote_non_finite(json: &[u8]) -> Cow<'_, [u8]> {
+        const LITERALS: [&[u8]; 3] = [b"NaN", b"Infinity", b"-Infinity"];
+
+        let is_word = |byte: &u8| byte.is_ascii_alphanumeric() || b"_.+-".contains(byte);
+        let mut quoted: Option<Vec<u8>> = None;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut start = 0;
+        let mut index = 0;
+
+        while index < json.len() {
+            let byte = json[index];
+
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => (),
+                }
+                index += 1;
+                continue;
+            }
+
+            if byte == b'"' {
+                in_string = true;
+                index += 1;
+                continue;
+            }
+
+            // A literal has to be a whole token, so that it is not mistaken
+            // for part of a number or of some other invalid word.
+            let literal = LITERALS.iter().find(|literal| {
+                json[index..].starts_with(literal)
+                    && (index == 0 || !is_word(&json[index - 1]))
+                    && json
+                        .get(index + literal.len())
+                        .is_none_or(|next| !is_word(next))
+            });
+
+            match literal {
+                Some(literal) => {
+                    let quoted = quoted.get_or_insert_with(|| Vec::with_capacity(json.len() + 8));
+                    quoted.extend_from_slice(&json[start..index]);
+                    quoted.push(b'"');
+                    quoted.extend_from_slice(literal);
+                    quoted.push(b'"');
+                    index += literal.len();
+                    start = index;
+                }
+                None => index += 1,
+            }
+        }
+
+        match quoted {
+            Some(mut quoted) => {
+                quoted.extend_from_slice(&json[start..]);
+                Cow::Owned(quoted)
+            }
+            None => Cow::Borrowed(json),
+        }
+    }
+
+    /// The string that a value that is not finite is written as.
+    fn non_finite_name(value: f64) -> Option<&'static str> {
+        if value.is_nan() {
+            Some("NaN")
+        } else if value == f64::INFINITY {
+            Some("Infinity")
+        } else if value == f64::NEG_INFINITY {
+            Some("-Infinity")
+        } else {
+            None
+        }
+    }
+
+    /// The same functions, but serializing values that are not finite as
+    /// `null`. Deserializing is the same.
+    ///
+    /// ```rust
+    /// #[serde(with = "awaur::serde_with::lenient_float::null")]
+    /// ```
+    pub mod null {
+        use serde::Serializer;
+
+        pub use super::deserialize;
+
+        /// ```rust
+        /// #[serde(serialize_with = "awaur::serde_with::lenient_float::null::serialize")]
+        /// ```
+        pub fn serialize<S>(value: &f64, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            if value.is_finite() {
+                serializer.serialize_f64(*value)
+            } else {
+                serializer.serialize_none()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde-as-wrapper")]
+mod wrapper {
+    use serde::{Deserializer, Serializer};
+    use serde_with::{DeserializeAs, SerializeAs};
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs].
+    pub struct LenientFloat;
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs], serializing values that
+    /// are not finite as `null`.
+    pub struct LenientFloatNull;
+
+    impl SerializeAs<f64> for LenientFloat {
+        fn serialize_as<S>(source: &f64, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::serialize(source, serializer)
+        }
+    }
+
+    impl<'de> DeserializeAs<'de, f64> for LenientFloat {
+        fn deserialize_as<D>(deserializer: D) -> Result<f64, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::deserialize(deserializer)
+        }
+    }
+
+    impl SerializeAs<f64> for LenientFloatNull {
+        fn serialize_as<S>(source: &f64, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::null::serialize(source, serializer)
+        }
+    }
+
+    impl<'de> DeserializeAs<'de, f64> for LenientFloatNull {
+        fn deserialize_as<D>(deserializer: D) -> Result<f64, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::with::null::deserialize(deserializer)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde-as-wrapper"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_with::serde_as;
+
+    use super::{quote_non_finite, LenientFloat, LenientFloatNull};
+
+    #[serde_as]
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Reading {
+        #[serde_as(as = "LenientFloat")]
+        value: f64,
+    }
+
+    #[serde_as]
+    #[derive(Debug, Serialize, Deserialize)]
+    struct StrictReading {
+        #[serde_as(as = "LenientFloatNull")]
+        value: f64,
+    }
+
+    fn value(json: &str) -> f64 {
+        serde_json::from_str::<Reading>(json).unwrap().value
+    }
+
+    #[test]
+    fn test_deserialize() {
+        assert!(value(r#"{"value":"NaN"}"#).is_nan());
+        assert_eq!(value(r#"{"value":"Infinity"}"#), f64::INFINITY);
+        assert_eq!(value(r#"{"value":"-Infinity"}"#), f64::NEG_INFINITY);
+        assert_eq!(value(r#"{"value":"-inf"}"#), f64::NEG_INFINITY);
+        assert_eq!(value(r#"{"value":1.5}"#), 1.5);
+        assert_eq!(value(r#"{"value":-3}"#), -3.0);
+        assert_eq!(value(r#"{"value":"2.25"}"#), 2.25);
+        assert!(value(r#"{"value":null}"#).is_nan());
+
+        assert!(serde_json::from_str::<Reading>(r#"{"value":"many"}"#).is_err());
+        // Not valid JSON, so `serde_json` rejects it before it gets here,
+        // unless the literals are quoted first.
+        assert!(serde_json::from_str::<Reading>(r#"{"value":NaN}"#).is_err());
+        let bare = |json: &str| {
+            serde_json::from_slice::<Reading>(&quote_non_finite(json.as_bytes()))
+                .unwrap()
+                .value
+        };
+        assert!(bare(r#"{"value":NaN}"#).is_nan());
+        assert_eq!(bare(r#"{"value":Infinity}"#), f64::INFINITY);
+        assert_eq!(bare(r#"{"value": -Infinity }"#), f64::NEG_INFINITY);
+        assert_eq!(bare(r#"{"value":1.5}"#), 1.5);
+    }
+
+    #[test]
+    fn test_quote_non_finite() {
+        let quote = |json: &str| String::from_utf8(quote_non_finite(json.as_bytes()).into_owned());
+
+        assert_eq!(
+            quote(r#"[NaN,Infinity, -Infinity,1e5]"#).unwrap(),
+            r#"["NaN","Infinity", "-Infinity",1e5]"#
+        );
+        // Strings, including escaped quotes, are left alone.
+        assert_eq!(
+            quote(r#"{"NaN":"say \"NaN\" Infinity","x":NaN}"#).unwrap(),
+            r#"{"NaN":"say \"NaN\" Infinity","x":"NaN"}"#
+        );
+        // Only whole tokens are literals.
+        assert_eq!(quote("[NaNa,xInfinity]").unwrap(), "[NaNa,xInfinity]");
+        assert!(matches!(
+            quote_non_finite(b"[1.5,null]"),
+            std::borrow::Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let json = |value| serde_json::to_string(&Reading { value }).unwrap();
+        let strict = |value| serde_json::to_string(&StrictReading { value }).unwrap();
+
+        assert_eq!(json(f64::NAN), r#"{"value":"NaN"}"#);
+        assert_eq!(json(f64::INFINITY), r#"{"value":"Infinity"}"#);
+        assert_eq!(json(f64::NEG_INFINITY), r#"{"value":"-Infinity"}"#);
+        assert_eq!(json(1.5), r#"{"value":1.5}"#);
+
+        assert_eq!(strict(f64::NAN), r#"{"value":null}"#);
+        assert_eq!(strict(f64::NEG_INFINITY), r#"{"value":null}"#);
+        assert_eq!(strict(1.5), r#"{"value":1.5}"#);
+    }
+}
@@ -0,0 +1,250 @@
+//! De/serialize `Vec<(K, V)>` as a map, where each key of the map becomes the
+//! first item of a tuple in the vector, and its value the second.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::EntriesVec")]
+//! ```
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::EntriesVec<DisplayFromStr, _>")]
+//! ```
+//! ```rust
+//! #[serde(serialize_with = "awaur::serde_with::map_as_entries::serialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::map_as_entries::deserialize")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::map_as_entries")]
+//! ```
+//!
+//! The order of the entries is preserved in both directions: the vector has
+//! the entries in the order that they appear in the serialized map, and they
+//! are serialized back in the order of the vector. Duplicate keys are kept, it
+//! is up to the format whether or not they are accepted.
+
+pub use with::*;
+#[doc(hidden)]
+#[cfg(feature = "serde-as-wrapper")]
+pub use wrapper::*;
+
+mod with {
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    use serde::de::{MapAccess, Visitor};
+    use serde::ser::SerializeMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// ```rust
+    /// #[serde(serialize_with = "awaur::serde_with::map_as_entries::serialize")]
+    /// ```
+    pub fn serialize<S, K, V>(value: &[(K, V)], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        K: Serialize,
+        V: Serialize,
+    {
+        let mut map = serializer.serialize_map(Some(value.len()))?;
+
+        for (key, value) in value {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.end()
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::map_as_entries::deserialize")]
+    /// ```
+    pub fn deserialize<'de, D, K, V>(deserializer: D) -> Result<Vec<(K, V)>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        deserializer.deserialize_map(_Visitor(PhantomData))
+    }
+
+    struct _Visitor<K, V>(PhantomData<(K, V)>);
+
+    impl<'de, K, V> Visitor<'de> for _Visitor<K, V>
+    where
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = Vec<(K, V)>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+
+            while let Some(entry) = map.next_entry()? {
+                entries.push(entry);
+            }
+
+            Ok(entries)
+        }
+    }
+}
+
+#[cfg(feature = "serde-as-wrapper")]
+mod wrapper {
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    use serde::de::{MapAccess, Visitor};
+    use serde::ser::SerializeMap;
+    use serde::{Deserializer, Serializer};
+    use serde_with::de::DeserializeAsWrap;
+    use serde_with::ser::SerializeAsWrap;
+    use serde_with::{DeserializeAs, Same, SerializeAs};
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs]. The keys and values are
+    /// themselves de/serialized with `K` and `V`, which default to
+    /// [`Same`][serde_with::Same].
+    pub struct EntriesVec<K = Same, V = Same>(PhantomData<(K, V)>);
+
+    impl<K, V, KAs, VAs> SerializeAs<Vec<(K, V)>> for EntriesVec<KAs, VAs>
+    where
+        KAs: SerializeAs<K>,
+        VAs: SerializeAs<V>,
+    {
+        fn serialize_as<S>(source: &Vec<(K, V)>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut map = serializer.serialize_map(Some(source.len()))?;
+
+            for (key, value) in source {
+                map.serialize_entry(
+                    &SerializeAsWrap::<K, KAs>::new(key),
+                    &SerializeAsWrap::<V, VAs>::new(value),
+                )?;
+            }
+
+            map.end()
+        }
+    }
+
+    impl<'de, K, V, KAs, VAs> DeserializeAs<'de, Vec<(K, V)>> for EntriesVec<KAs, VAs>
+    where
+        KAs: DeserializeAs<'de, K>,
+        VAs: DeserializeAs<'de, V>,
+    {
+        fn deserialize_as<D>(deserializer: D) -> Result<Vec<(K, V)>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_map(_Visitor::<K, V, KAs, VAs>(PhantomData))
+        }
+    }
+
+    struct _Visitor<K, V, KAs, VAs>(PhantomData<(K, V, KAs, VAs)>);
+
+    impl<'de, K, V, KAs, VAs> Visitor<'de> for _Visitor<K, V, KAs, VAs>
+    where
+        KAs: DeserializeAs<'de, K>,
+        VAs: DeserializeAs<'de, V>,
+    {
+        type Value = Vec<(K, V)>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+
+            while let Some((key, value)) =
+                map.next_entry::<DeserializeAsWrap<K, KAs>, DeserializeAsWrap<V, VAs>>()?
+            {
+                entries.push((key.into_inner(), value.into_inner()));
+            }
+
+            Ok(entries)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde-as-wrapper"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_with::{serde_as, DisplayFromStr};
+
+    use super::EntriesVec;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Project {
+        name: String,
+    }
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Projects {
+        #[serde_as(as = "EntriesVec")]
+        by_id: Vec<(u32, Project)>,
+    }
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Counts {
+        #[serde_as(as = "EntriesVec<_, DisplayFromStr>")]
+        by_name: Vec<(String, u64)>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct WithProjects {
+        #[serde(with = "super")]
+        by_id: Vec<(u32, Project)>,
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        // The keys are deliberately out of order, to check that it is kept.
+        let json =
+            r#"{"by_id":{"3":{"name":"awaur"},"1":{"name":"modrinth"},"2":{"name":"curseforge"}}}"#;
+        let projects = serde_json::from_str::<Projects>(json).unwrap();
+
+        let project = |name: &str| Project {
+            name: name.to_owned(),
+        };
+
+        assert_eq!(
+            projects.by_id,
+            [
+                (3, project("awaur")),
+                (1, project("modrinth")),
+                (2, project("curseforge")),
+            ]
+        );
+        assert_eq!(serde_json::to_string(&projects).unwrap(), json);
+
+        // The plain functions should behave the same as the wrapper.
+        let projects = serde_json::from_str::<WithProjects>(json).unwrap();
+
+        assert_eq!(projects.by_id.len(), 3);
+        assert_eq!(serde_json::to_string(&projects).unwrap(), json);
+    }
+
+    #[test]
+    fn test_nested_as() {
+        let json = r#"{"by_name":{"stars":"12","forks":"3"}}"#;
+        let counts = serde_json::from_str::<Counts>(json).unwrap();
+
+        assert_eq!(
+            counts.by_name,
+            [("stars".to_owned(), 12), ("forks".to_owned(), 3)]
+        );
+        assert_eq!(serde_json::to_string(&counts).unwrap(), json);
+    }
+}
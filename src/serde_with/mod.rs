@@ -1,12 +1,50 @@
 //! Provides types and functions that can be used with either `#[serde(with =
 //! "...")]` or `#[serde_as(as = "...")]`.
 
+#[cfg(feature = "serde-with-always-null")]
+pub mod always_null;
 #[cfg(feature = "serde-with-base62")]
 pub mod base62;
+#[cfg(feature = "serde-with-base64")]
+pub mod base64;
+#[cfg(feature = "serde-with-bitflags")]
+pub mod bitflags;
+#[cfg(feature = "serde-with-delimited")]
+pub mod delimited;
+#[cfg(feature = "serde-with-hex")]
+pub mod hex;
+#[cfg(feature = "serde-with-iso8601-duration")]
+pub mod iso8601_duration;
 #[cfg(feature = "serde-with-json-string")]
 pub mod json_string;
+#[cfg(feature = "serde-with-lenient-float")]
+pub mod lenient_float;
+#[cfg(feature = "serde-with-map-as-entries")]
+pub mod map_as_entries;
+#[cfg(feature = "serde-with-unwrap-single")]
+pub mod unwrap_single;
 
+#[cfg(all(feature = "serde-with-always-null", feature = "serde-as-wrapper"))]
+pub use self::always_null::AlwaysNull;
+#[cfg(all(feature = "serde-with-base62-cache", feature = "serde-as-wrapper"))]
+pub use self::base62::Base62Cached;
 #[cfg(all(feature = "serde-with-base62", feature = "serde-as-wrapper"))]
-pub use self::base62::Base62;
+pub use self::base62::{Base62, Base62Signed};
+#[cfg(all(feature = "serde-with-base64", feature = "serde-as-wrapper"))]
+pub use self::base64::{Base64, Base64UrlSafe};
+#[cfg(all(feature = "serde-with-bitflags", feature = "serde-as-wrapper"))]
+pub use self::bitflags::{BitFlags, BitFlagsPreserving};
+#[cfg(all(feature = "serde-with-delimited", feature = "serde-as-wrapper"))]
+pub use self::delimited::{CommaSeparated, Delimited};
+#[cfg(all(feature = "serde-with-hex", feature = "serde-as-wrapper"))]
+pub use self::hex::{Hex, HexUppercase};
+#[cfg(all(feature = "serde-with-iso8601-duration", feature = "serde-as-wrapper"))]
+pub use self::iso8601_duration::Iso8601Duration;
 #[cfg(all(feature = "serde-with-json-string", feature = "serde-as-wrapper"))]
 pub use self::json_string::JsonString;
+#[cfg(all(feature = "serde-with-lenient-float", feature = "serde-as-wrapper"))]
+pub use self::lenient_float::{LenientFloat, LenientFloatNull};
+#[cfg(all(feature = "serde-with-map-as-entries", feature = "serde-as-wrapper"))]
+pub use self::map_as_entries::EntriesVec;
+#[cfg(all(feature = "serde-with-unwrap-single", feature = "serde-as-wrapper"))]
+pub use self::unwrap_single::{UnwrapFirst, UnwrapSingle};
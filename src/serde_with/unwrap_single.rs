@@ -0,0 +1,237 @@
+//! De/serialize `T` as either `T` itself, or an array containing only `T`.
+//!
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::UnwrapSingle")]
+//! ```
+//! ```rust
+//! #[serde_as(as = "awaur::serde_with::UnwrapFirst<...>")]
+//! ```
+//! ```rust
+//! #[serde(serialize_with = "awaur::serde_with::unwrap_single::serialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::unwrap_single::deserialize")]
+//! ```
+//! ```rust
+//! #[serde(deserialize_with = "awaur::serde_with::unwrap_single::deserialize_first")]
+//! ```
+//! ```rust
+//! #[serde(with = "awaur::serde_with::unwrap_single")]
+//! ```
+//!
+//! This is for APIs that are inconsistent about whether a field is a value, or
+//! an array of one value. When deserializing, both are accepted. An array with
+//! more than one element is an error with [`deserialize`] (and
+//! [`UnwrapSingle`]), but [`deserialize_first`] (and [`UnwrapFirst`]) takes
+//! the first element and ignores the rest. An empty array is always an error.
+//! Values are always serialized without an array.
+//!
+//! Because a bare value is tried after an array, `T` should not itself be
+//! deserializable from an array.
+
+pub use with::*;
+#[doc(hidden)]
+#[cfg(feature = "serde-as-wrapper")]
+pub use wrapper::*;
+
+mod with {
+    use serde::de::Error as DeserializeError;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        Many(Vec<T>),
+        One(T),
+    }
+
+    /// ```rust
+    /// #[serde(serialize_with = "awaur::serde_with::unwrap_single::serialize")]
+    /// ```
+    pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        value.serialize(serializer)
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::unwrap_single::deserialize")]
+    /// ```
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        unwrap(deserializer, false)
+    }
+
+    /// ```rust
+    /// #[serde(deserialize_with = "awaur::serde_with::unwrap_single::deserialize_first")]
+    /// ```
+    pub fn deserialize_first<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        unwrap(deserializer, true)
+    }
+
+    fn unwrap<'de, D, T>(deserializer: D, first: bool) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(value) => Ok(value),
+            OneOrMany::Many(values) if values.len() == 1 || (first && !values.is_empty()) => {
+                // Use of unwrap:
+                // The vector was just checked to not be empty.
+                Ok(values.into_iter().next().unwrap())
+            }
+            OneOrMany::Many(values) => Err(DeserializeError::invalid_length(
+                values.len(),
+                &"a single value, or an array of one",
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "serde-as-wrapper")]
+mod wrapper {
+    use std::marker::PhantomData;
+
+    use serde::{Deserializer, Serializer};
+    use serde_with::de::DeserializeAsWrap;
+    use serde_with::ser::SerializeAsWrap;
+    use serde_with::{DeserializeAs, Same, SerializeAs};
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs], rejecting arrays of more
+    /// than one element.
+    pub struct UnwrapSingle<T = Same>(PhantomData<T>);
+
+    /// Implements [`SerializeAs`][serde_with::SerializeAs] and
+    /// [`DeserializeAs`][serde_with::DeserializeAs], taking the first element
+    /// of an array of more than one.
+    pub struct UnwrapFirst<T = Same>(PhantomData<T>);
+
+    impl<T, U> SerializeAs<T> for UnwrapSingle<U>
+    where
+        U: SerializeAs<T>,
+    {
+        fn serialize_as<S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::serialize(&SerializeAsWrap::<T, U>::new(source), serializer)
+        }
+    }
+
+    impl<'de, T, U> DeserializeAs<'de, T> for UnwrapSingle<U>
+    where
+        U: DeserializeAs<'de, T>,
+    {
+        fn deserialize_as<D>(deserializer: D) -> Result<T, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let wrapped: DeserializeAsWrap<T, U> = super::with::deserialize(deserializer)?;
+            Ok(wrapped.into_inner())
+        }
+    }
+
+    impl<T, U> SerializeAs<T> for UnwrapFirst<U>
+    where
+        U: SerializeAs<T>,
+    {
+        fn serialize_as<S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            super::with::serialize(&SerializeAsWrap::<T, U>::new(source), serializer)
+        }
+    }
+
+    impl<'de, T, U> DeserializeAs<'de, T> for UnwrapFirst<U>
+    where
+        U: DeserializeAs<'de, T>,
+    {
+        fn deserialize_as<D>(deserializer: D) -> Result<T, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let wrapped: DeserializeAsWrap<T, U> = super::with::deserialize_first(deserializer)?;
+            Ok(wrapped.into_inner())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde-as-wrapper"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_with::serde_as;
+
+    use super::{UnwrapFirst, UnwrapSingle};
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Release {
+        #[serde_as(as = "UnwrapSingle")]
+        version: String,
+    }
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct LenientRelease {
+        #[serde_as(as = "UnwrapFirst")]
+        version: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct WithRelease {
+        #[serde(with = "super")]
+        version: u32,
+    }
+
+    #[test]
+    fn test_array_of_one() {
+        let release = serde_json::from_str::<Release>(r#"{"version":["1.0.0"]}"#).unwrap();
+
+        assert_eq!(release.version, "1.0.0");
+        assert_eq!(
+            serde_json::to_string(&release).unwrap(),
+            r#"{"version":"1.0.0"}"#
+        );
+
+        let release = serde_json::from_str::<WithRelease>(r#"{"version":[3]}"#).unwrap();
+
+        assert_eq!(release.version, 3);
+        assert_eq!(serde_json::to_string(&release).unwrap(), r#"{"version":3}"#);
+    }
+
+    #[test]
+    fn test_bare_scalar() {
+        let release = serde_json::from_str::<Release>(r#"{"version":"1.0.0"}"#).unwrap();
+
+        assert_eq!(release.version, "1.0.0");
+
+        let release = serde_json::from_str::<WithRelease>(r#"{"version":3}"#).unwrap();
+
+        assert_eq!(release.version, 3);
+    }
+
+    #[test]
+    fn test_multiple_elements() {
+        let json = r#"{"version":["1.0.0","2.0.0"]}"#;
+
+        assert!(serde_json::from_str::<Release>(json).is_err());
+        assert!(serde_json::from_str::<WithRelease>(r#"{"version":[3,4]}"#).is_err());
+        assert!(serde_json::from_str::<LenientRelease>(r#"{"version":[]}"#).is_err());
+
+        let release = serde_json::from_str::<LenientRelease>(json).unwrap();
+
+        assert_eq!(release.version, "1.0.0");
+    }
+}